@@ -1,5 +1,7 @@
-use array2d::Array2D;
-use shared::{Adjacents, ChallengeDay, Question, adjacent_positions, get_question_data_to_grid};
+use shared::{
+    Adjacents, ChallengeDay, Question, UpdateMode, get_question_data_to_grid, run_automaton,
+    step_automaton,
+};
 
 fn main() {
     let t_a = std::thread::spawn(|| {
@@ -16,46 +18,37 @@ fn main() {
 
 fn part_a(question: Question) -> u32 {
     println!("Starting Part A");
-    let input_file = get_question_data_to_grid(ChallengeDay::Day4, question);
+    let mut input_file = get_question_data_to_grid(ChallengeDay::Day4, question);
     // For every cell that has an `@` check if the surrounding 8 cells have less than 4 @'s
-    find_valid_ones(&input_file).len() as u32
+    step_automaton(
+        &mut input_file,
+        Adjacents::ALL,
+        UpdateMode::Synchronous,
+        remove_accessible,
+    ) as u32
 }
 
 fn part_b(question: Question) -> u64 {
     println!("Starting Part B");
     let mut input_file = get_question_data_to_grid(ChallengeDay::Day4, question);
-    // For every cell that has an `@` check if the surrounding 8 cells have less than 4 @'s
-    let mut valid_count = 0;
-    let mut removed = true;
-    while removed {
-        removed = false;
-        for pos in find_valid_ones(&input_file) {
-            input_file.set(pos.0, pos.1, b'.').unwrap();
-            valid_count += 1;
-            removed = true;
-        }
-    }
-    valid_count
+    // Keep removing accessible rolls until none are left
+    let run = run_automaton(
+        &mut input_file,
+        Adjacents::ALL,
+        UpdateMode::Synchronous,
+        usize::MAX,
+        remove_accessible,
+    );
+    run.total_changes as u64
 }
 
-fn find_valid_ones(input_file: &Array2D<u8>) -> Vec<(usize, usize)> {
-    let mut valid_positions = Vec::new();
-    for pos in input_file
-        .enumerate_row_major()
-        .filter_map(|(p, v)| if *v == b'@' { Some(p) } else { None })
-    {
-        let mut count = 0;
-        for new_pos in adjacent_positions(input_file, pos, Adjacents::ALL) {
-            if new_pos != pos && input_file.get(new_pos.0, new_pos.1).unwrap_or(&b' ') == &b'@' {
-                count += 1;
-            }
-        }
-
-        if count < 4 {
-            valid_positions.push(pos);
-        }
+// A roll (`@`) with fewer than 4 rolls around it can be removed
+fn remove_accessible(cell: &u8, neighbours: &[u8]) -> u8 {
+    if *cell == b'@' && neighbours.iter().filter(|n| **n == b'@').count() < 4 {
+        b'.'
+    } else {
+        *cell
     }
-    valid_positions
 }
 
 #[cfg(test)]
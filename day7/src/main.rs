@@ -1,8 +1,8 @@
 use array2d::Array2D;
 use memoize::memoize;
 use shared::{
-    ArrayPosition, ChallengeDay, Directions, Question, get_question_data_to_grid,
-    move_grid_cursor_by_direction, print_array,
+    ArrayPosition, ChallengeDay, Directions, Question, cast_ray, get_question_data_to_grid,
+    get_question_data_to_grid_with_markers, move_grid_cursor_by_direction, print_array,
 };
 fn main() {
     let t_a = std::thread::spawn(|| {
@@ -69,21 +69,16 @@ fn recursively_explore_grid(
     beam_at: ArrayPosition,
     counter: usize,
 ) -> usize {
-    let row = beam_at.0 + 1;
-    if row == input_file.num_rows() {
-        return 1;
-    }
-    // Walk across the row, copy rays down and fork on splitters
-
-    let current = *input_file.get(row, beam_at.1).unwrap();
-
-    if current == b'.' {
-        return recursively_explore_grid(input_file, (row, beam_at.1), counter);
-    }
-    if current == b'^' {
-        return recursively_explore_splitter(input_file, (row, beam_at.1), counter);
+    // Follow the beam down until it hits something, forking on splitters
+    let down = Directions::DOWN.get_direction();
+    match cast_ray(input_file, beam_at, down, |cell| *cell != b'.') {
+        // Made it out the bottom
+        None => 1,
+        Some(hit) if input_file[hit] == b'^' => {
+            recursively_explore_splitter(input_file, hit, counter)
+        }
+        Some(_) => 0,
     }
-    0
 }
 fn recursively_explore_splitter(
     input_file: &Array2D<u8>,
@@ -111,11 +106,9 @@ fn recursively_explore_splitter(
 
 fn part_b(question: Question) -> usize {
     println!("Starting Part B");
-    let input_file = get_question_data_to_grid(ChallengeDay::Day7, question);
-    let start_col = (0..input_file.num_columns())
-        .find(|&col| input_file.get(0, col) == Some(&b'S'))
-        .unwrap();
-    recursively_explore_grid(&input_file, (0, start_col), 0)
+    let (input_file, markers) =
+        get_question_data_to_grid_with_markers(ChallengeDay::Day7, question, b"S", None);
+    recursively_explore_grid(&input_file, markers[&b'S'][0], 0)
 }
 
 #[cfg(test)]
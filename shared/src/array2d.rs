@@ -1,54 +1,34 @@
+use std::collections::VecDeque;
+
 use array2d::Array2D;
+
+use crate::{RenderOptions, render_array, rotate_array_90};
 pub type ArrayPosition = (usize, usize);
 
-pub fn rotate_array(mut grid: Array2D<u8>) -> Array2D<u8> {
+/// Build a grid of bytes from text lines, for writing test grids inline
+#[cfg(test)]
+pub(crate) fn grid_from_lines(lines: &[&str]) -> Array2D<u8> {
+    let rows: Vec<Vec<u8>> = lines.iter().map(|l| l.as_bytes().to_vec()).collect();
+    Array2D::from_rows(&rows).unwrap()
+}
+
+pub fn rotate_array<T: Clone>(grid: Array2D<T>) -> Array2D<T> {
     //Rotate the array 90 deg to the right
-    // Do this by transposing the array, then reversing the rows
-    assert_eq!(grid.row_len(), grid.column_len());
-
-    //Transpose
-    for r in 0..grid.num_rows() {
-        for c in 0..r {
-            let tmp = *grid.get(r, c).unwrap();
-            let tmp2 = *grid.get(c, r).unwrap();
-            grid.set(r, c, tmp2).unwrap();
-            grid.set(c, r, tmp).unwrap();
-        }
-    }
-    // Flip each row horizontally
-    for r in 0..grid.num_rows() {
-        for c in 0..grid.num_columns() / 2 {
-            let tmp = *grid.get(r, c).unwrap();
-            grid.set(r, c, *grid.get(r, grid.num_columns() - c - 1).unwrap())
-                .unwrap();
-            grid.set(r, grid.num_columns() - c - 1, tmp).unwrap();
-        }
-    }
-    grid
+    rotate_array_90(&grid)
 }
 pub fn print_array(array: &Array2D<u8>) {
     println!();
-    for row_iter in array.rows_iter() {
-        for element in row_iter {
-            print!("{}", str::from_utf8(&[*element]).unwrap());
-        }
-        println!();
-    }
+    print!("{}", render_array(array, &RenderOptions::default()));
     println!();
 }
 
 pub fn print_array_bool(array: &Array2D<bool>) {
-    println!();
-    for row_iter in array.rows_iter() {
-        for element in row_iter {
-            match element {
-                true => print!("#"),
-                false => print!("."),
-            }
-        }
-        println!();
-    }
-    println!();
+    let elements = array
+        .elements_row_major_iter()
+        .map(|element| if *element { b'#' } else { b'.' });
+    let array =
+        Array2D::from_iter_row_major(elements, array.num_rows(), array.num_columns()).unwrap();
+    print_array(&array);
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -100,8 +80,8 @@ impl Directions {
         }
     }
 }
-pub fn move_grid_cursor_by_direction(
-    array: &Array2D<u8>,
+pub fn move_grid_cursor_by_direction<T>(
+    array: &Array2D<T>,
     direction: Directions,
     cursor: ArrayPosition,
 ) -> Option<ArrayPosition> {
@@ -123,34 +103,109 @@ pub fn array_movement_sequence(
     end_pos: ArrayPosition,
     banned_position_char: u8,
 ) -> Option<Vec<Directions>> {
-    // Find the optimal path from start to end, without stepping on any grid positions with the banned character
-    let mut current_position = start_pos;
-    let mut steps = Vec::with_capacity(array.num_rows() * array.num_columns() / 2);
-    while current_position != end_pos {
-        let mut best_direction = None;
-        let mut best_distance = i64::MAX;
+    // Breadth first search from the start, recording the move used to first reach each position
+    // The first visit to a position is always along a shortest path
+    let mut arrived_by: Array2D<Option<Directions>> =
+        Array2D::filled_with(None, array.num_rows(), array.num_columns());
+    let mut queue = VecDeque::from([start_pos]);
+    while let Some(current_position) = queue.pop_front() {
+        if current_position == end_pos {
+            break;
+        }
         for direction in Directions::all() {
-            let next_position = move_grid_cursor_by_direction(array, direction, current_position);
-            if let Some(next_position) = next_position {
-                let distance = (next_position.0 as i64 - end_pos.0 as i64).abs()
-                    + (next_position.1 as i64 - end_pos.1 as i64).abs();
-                if distance < best_distance && array[next_position] != banned_position_char {
-                    best_direction = Some(direction);
-                    best_distance = distance;
-                }
+            if let Some(next_position) =
+                move_grid_cursor_by_direction(array, direction, current_position)
+                && next_position != start_pos
+                && arrived_by[next_position].is_none()
+                && array[next_position] != banned_position_char
+            {
+                arrived_by[next_position] = Some(direction);
+                queue.push_back(next_position);
             }
         }
-        if let Some(direction) = best_direction {
-            steps.push(direction);
-            current_position =
-                move_grid_cursor_by_direction(array, direction, current_position).unwrap();
-        } else {
-            return None;
-        }
     }
+    if start_pos != end_pos && arrived_by[end_pos].is_none() {
+        return None;
+    }
+
+    // Walk back from the end to recover the moves
+    let mut steps = Vec::new();
+    let mut current_position = end_pos;
+    while current_position != start_pos {
+        let direction = arrived_by[current_position].unwrap();
+        steps.push(direction);
+        current_position =
+            move_grid_cursor_by_direction(array, direction.opposite(), current_position).unwrap();
+    }
+    steps.reverse();
     Some(steps)
 }
 
+/// Every shortest movement sequence from start to end, avoiding the banned character
+/// When `minimise_turns` is set, only the sequences with the fewest changes of direction are kept
+/// (i.e. for keypad style puzzles `>>^` is preferred over `>^>`)
+/// Returns an empty list if there is no path
+pub fn all_shortest_movement_sequences(
+    array: &Array2D<u8>,
+    start_pos: ArrayPosition,
+    end_pos: ArrayPosition,
+    banned_position_char: u8,
+    minimise_turns: bool,
+) -> Vec<Vec<Directions>> {
+    if start_pos != end_pos && array[end_pos] == banned_position_char {
+        return vec![];
+    }
+    // Distances to the end, so from the start we can just follow any move that gets one step closer
+    let mut distance_to_end: Array2D<Option<usize>> =
+        Array2D::filled_with(None, array.num_rows(), array.num_columns());
+    distance_to_end[end_pos] = Some(0);
+    let mut queue = VecDeque::from([end_pos]);
+    while let Some(current_position) = queue.pop_front() {
+        let distance = distance_to_end[current_position].unwrap();
+        for direction in Directions::all() {
+            if let Some(next_position) =
+                move_grid_cursor_by_direction(array, direction, current_position)
+                && distance_to_end[next_position].is_none()
+                && (next_position == start_pos || array[next_position] != banned_position_char)
+            {
+                distance_to_end[next_position] = Some(distance + 1);
+                queue.push_back(next_position);
+            }
+        }
+    }
+    if distance_to_end[start_pos].is_none() {
+        return vec![];
+    }
+
+    let mut sequences = Vec::new();
+    let mut pending = vec![(start_pos, Vec::new())];
+    while let Some((current_position, steps)) = pending.pop() {
+        if current_position == end_pos {
+            sequences.push(steps);
+            continue;
+        }
+        let distance = distance_to_end[current_position].unwrap();
+        for direction in Directions::all().into_iter().rev() {
+            if let Some(next_position) =
+                move_grid_cursor_by_direction(array, direction, current_position)
+                && distance_to_end[next_position] == Some(distance - 1)
+            {
+                let mut next_steps = steps.clone();
+                next_steps.push(direction);
+                pending.push((next_position, next_steps));
+            }
+        }
+    }
+
+    if minimise_turns {
+        let count_turns =
+            |steps: &Vec<Directions>| steps.windows(2).filter(|pair| pair[0] != pair[1]).count();
+        let fewest_turns = sequences.iter().map(count_turns).min().unwrap_or(0);
+        sequences.retain(|steps| count_turns(steps) == fewest_turns);
+    }
+    sequences
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Adjacents {
     ALL,        //All below
@@ -161,7 +216,7 @@ pub enum Adjacents {
 }
 
 impl Adjacents {
-    pub fn get_pattern(&self) -> &[(i64, i64)] {
+    pub fn get_pattern(&self) -> &'static [(i64, i64)] {
         match self {
             Adjacents::ALL => &[
                 (-1, -1),
@@ -180,31 +235,64 @@ impl Adjacents {
         }
     }
 }
-pub fn adjacent_positions(
-    grid: &Array2D<u8>,
+pub fn adjacent_positions<T>(
+    grid: &Array2D<T>,
     point: ArrayPosition,
     adjacents: Adjacents,
 ) -> Vec<ArrayPosition> {
-    let (row, col) = point;
-    let mut positions = Vec::new();
+    adjacent_positions_iter(grid, point, adjacents).collect()
+}
 
-    let directions = adjacents.get_pattern();
+/// Same as `adjacent_positions` but without allocating, for use in hot loops
+pub fn adjacent_positions_iter<T>(
+    grid: &Array2D<T>,
+    point: ArrayPosition,
+    adjacents: Adjacents,
+) -> impl Iterator<Item = ArrayPosition> + use<T> {
+    let (row, col) = point;
+    let (num_rows, num_columns) = (grid.num_rows(), grid.num_columns());
 
-    for (dr, dc) in directions {
+    adjacents.get_pattern().iter().filter_map(move |(dr, dc)| {
         let new_row = row as i64 + dr;
         let new_col = col as i64 + dc;
 
-        if new_row >= 0 && new_col >= 0 {
-            let new_row = new_row as usize;
-            let new_col = new_col as usize;
-
-            if new_row < grid.num_rows() && new_col < grid.num_columns() {
-                positions.push((new_row, new_col));
-            }
+        if new_row >= 0
+            && new_col >= 0
+            && (new_row as usize) < num_rows
+            && (new_col as usize) < num_columns
+        {
+            Some((new_row as usize, new_col as usize))
+        } else {
+            None
         }
-    }
+    })
+}
 
-    positions
+/// Inclusive bounding box of a set of grid positions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct GridBounds {
+    pub min: ArrayPosition,
+    pub max: ArrayPosition,
+}
+
+impl GridBounds {
+    pub fn new(pos: ArrayPosition) -> Self {
+        GridBounds { min: pos, max: pos }
+    }
+    /// Grow the bounds to cover pos
+    pub fn include(&mut self, pos: ArrayPosition) {
+        self.min = (self.min.0.min(pos.0), self.min.1.min(pos.1));
+        self.max = (self.max.0.max(pos.0), self.max.1.max(pos.1));
+    }
+    pub fn contains(&self, pos: ArrayPosition) -> bool {
+        pos.0 >= self.min.0 && pos.0 <= self.max.0 && pos.1 >= self.min.1 && pos.1 <= self.max.1
+    }
+    pub fn num_rows(&self) -> usize {
+        self.max.0 - self.min.0 + 1
+    }
+    pub fn num_columns(&self) -> usize {
+        self.max.1 - self.min.1 + 1
+    }
 }
 
 #[cfg(test)]
@@ -297,6 +385,31 @@ mod tests {
         expected.sort();
         assert_eq!(sorted_result, expected);
     }
+    #[test]
+    fn test_adjacent_positions_iter_matches_vec() {
+        let grid = Array2D::filled_with(0u8, 2, 4);
+        for adjacents in [Adjacents::ALL, Adjacents::CROSS, Adjacents::DIAGONAL] {
+            for pos in grid.indices_row_major() {
+                let iterated: Vec<ArrayPosition> =
+                    adjacent_positions_iter(&grid, pos, adjacents).collect();
+                assert_eq!(iterated, adjacent_positions(&grid, pos, adjacents));
+            }
+        }
+    }
+
+    #[test]
+    fn test_grid_bounds() {
+        let mut bounds = GridBounds::new((3, 4));
+        bounds.include((1, 6));
+        bounds.include((2, 2));
+        assert_eq!(bounds.min, (1, 2));
+        assert_eq!(bounds.max, (3, 6));
+        assert_eq!(bounds.num_rows(), 3);
+        assert_eq!(bounds.num_columns(), 5);
+        assert!(bounds.contains((2, 4)));
+        assert!(!bounds.contains((0, 4)));
+    }
+
     #[test]
     fn test_array_movement_sequence_simple_path() {
         let grid = Array2D::filled_with(b'.', 5, 5);
@@ -369,6 +482,46 @@ mod tests {
             assert_eq!(direction, Directions::RIGHT);
         }
     }
+    #[test]
+    fn test_array_movement_sequence_concave_obstacle() {
+        // The greedy walk used to get stuck in the bottom of the U
+        let grid = grid_from_lines(&[".......", ".#...#.", ".#.S.#.", ".#####.", "...E..."]);
+        let result = array_movement_sequence(&grid, (2, 3), (4, 3), b'#').unwrap();
+        // Up 2, across 3, down 4, back 3
+        assert_eq!(result.len(), 12);
+        let mut current = (2, 3);
+        for direction in result {
+            current = move_grid_cursor_by_direction(&grid, direction, current).unwrap();
+            assert_ne!(grid[current], b'#');
+        }
+        assert_eq!(current, (4, 3));
+    }
+
+    #[test]
+    fn test_all_shortest_movement_sequences() {
+        // Keypad layout with a gap in the bottom left
+        let keypad = grid_from_lines(&["789", "456", "123", "#0A"]);
+
+        // From A to 7 is 3 up and 2 left in any order, but can't pass over the gap
+        let all = all_shortest_movement_sequences(&keypad, (3, 2), (0, 0), b'#', false);
+        assert_eq!(all.len(), 9);
+        assert!(all.iter().all(|steps| steps.len() == 5));
+        assert!(
+            all.iter()
+                .all(|steps| steps[..2] != [Directions::LEFT, Directions::LEFT])
+        );
+
+        let fewest_turns = all_shortest_movement_sequences(&keypad, (3, 2), (0, 0), b'#', true);
+        use Directions::*;
+        assert_eq!(fewest_turns, vec![vec![UP, UP, UP, LEFT, LEFT]]);
+
+        assert_eq!(
+            all_shortest_movement_sequences(&keypad, (1, 1), (1, 1), b'#', true),
+            vec![vec![]]
+        );
+        assert!(all_shortest_movement_sequences(&keypad, (0, 0), (3, 0), b'#', false).is_empty());
+    }
+
     #[test]
     fn test_directions_get_direction() {
         assert_eq!(Directions::UP.get_direction(), (-1, 0));
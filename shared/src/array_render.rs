@@ -0,0 +1,238 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use array2d::Array2D;
+
+use crate::ArrayPosition;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AnsiColour {
+    Black,
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    White,
+}
+
+impl AnsiColour {
+    fn code(&self) -> u8 {
+        match self {
+            AnsiColour::Black => 0,
+            AnsiColour::Red => 1,
+            AnsiColour::Green => 2,
+            AnsiColour::Yellow => 3,
+            AnsiColour::Blue => 4,
+            AnsiColour::Magenta => 5,
+            AnsiColour::Cyan => 6,
+            AnsiColour::White => 7,
+        }
+    }
+    pub fn foreground(&self) -> String {
+        format!("\x1b[3{}m", self.code())
+    }
+    pub fn background(&self) -> String {
+        format!("\x1b[4{}m", self.code())
+    }
+}
+const ANSI_RESET: &str = "\x1b[0m";
+
+/// A set of cells drawn over the top of the grid
+/// `symbol` replaces the cell's own byte when set, `colour` is drawn as the cell background
+#[derive(Debug, Clone, Default)]
+pub struct Overlay {
+    pub cells: Vec<(ArrayPosition, Option<u8>)>,
+    pub colour: Option<AnsiColour>,
+}
+
+impl Overlay {
+    /// Colour the background of the positions, leaving the cell contents visible
+    pub fn highlight(positions: &[ArrayPosition], colour: AnsiColour) -> Self {
+        Overlay {
+            cells: positions.iter().map(|pos| (*pos, None)).collect(),
+            colour: Some(colour),
+        }
+    }
+    /// Replace the positions with a fixed symbol
+    pub fn marker(positions: &[ArrayPosition], symbol: u8, colour: Option<AnsiColour>) -> Self {
+        Overlay {
+            cells: positions.iter().map(|pos| (*pos, Some(symbol))).collect(),
+            colour,
+        }
+    }
+    /// Draw a path (such as from `maze_shortest_path`) as arrows showing the direction of travel
+    /// The final position keeps its own byte so the end point stays visible
+    pub fn path(path: &[ArrayPosition], colour: Option<AnsiColour>) -> Self {
+        let mut cells: Vec<(ArrayPosition, Option<u8>)> = path
+            .windows(2)
+            .map(|step| {
+                let (from, to) = (step[0], step[1]);
+                let symbol = match (to.0 as i64 - from.0 as i64, to.1 as i64 - from.1 as i64) {
+                    (-1, 0) => b'^',
+                    (1, 0) => b'v',
+                    (0, -1) => b'<',
+                    (0, 1) => b'>',
+                    _ => b'*',
+                };
+                (from, Some(symbol))
+            })
+            .collect();
+        if let Some(last) = path.last() {
+            cells.push((*last, None));
+        }
+        Overlay { cells, colour }
+    }
+}
+
+/// Options for `render_array`, the default renders the grid as plain text
+#[derive(Debug, Clone, Default)]
+pub struct RenderOptions {
+    /// Drawn in order, so later overlays win where they overlap
+    pub overlays: Vec<Overlay>,
+    /// Foreground colour per cell value
+    pub value_colours: HashMap<u8, AnsiColour>,
+    /// Emit ANSI escape codes, when false all colours are ignored
+    pub use_colour: bool,
+    /// Add column numbers above and row numbers to the left of the grid
+    pub rulers: bool,
+}
+
+/// Render the grid to a string, one line per row with a trailing newline
+pub fn render_array(array: &Array2D<u8>, options: &RenderOptions) -> String {
+    // Resolve the overlays into the final symbol + background per cell
+    let mut overlay_cells: HashMap<ArrayPosition, (Option<u8>, Option<AnsiColour>)> =
+        HashMap::new();
+    for overlay in &options.overlays {
+        for (pos, symbol) in &overlay.cells {
+            let cell = overlay_cells.entry(*pos).or_insert((None, None));
+            if symbol.is_some() {
+                cell.0 = *symbol;
+            }
+            if overlay.colour.is_some() {
+                cell.1 = overlay.colour;
+            }
+        }
+    }
+
+    let row_label_width = if options.rulers {
+        array.num_rows().saturating_sub(1).to_string().len()
+    } else {
+        0
+    };
+    let mut output = String::new();
+    if options.rulers {
+        render_column_rulers(&mut output, array.num_columns(), row_label_width);
+    }
+
+    for (row, row_iter) in array.rows_iter().enumerate() {
+        if options.rulers {
+            write!(output, "{row:>row_label_width$} ").unwrap();
+        }
+        for (col, element) in row_iter.enumerate() {
+            let (symbol, background) = overlay_cells
+                .get(&(row, col))
+                .copied()
+                .unwrap_or((None, None));
+            let symbol = symbol.unwrap_or(*element);
+            let foreground = options.value_colours.get(&symbol);
+            let styled = options.use_colour && (foreground.is_some() || background.is_some());
+            if options.use_colour {
+                if let Some(colour) = foreground {
+                    output.push_str(&colour.foreground());
+                }
+                if let Some(colour) = background {
+                    output.push_str(&colour.background());
+                }
+            }
+            output.push(symbol as char);
+            if styled {
+                output.push_str(ANSI_RESET);
+            }
+        }
+        output.push('\n');
+    }
+    output
+}
+
+fn render_column_rulers(output: &mut String, num_columns: usize, row_label_width: usize) {
+    // One line per decimal digit of the largest column index, most significant first
+    let digits = num_columns.saturating_sub(1).to_string().len();
+    for digit in (0..digits).rev() {
+        output.push_str(&" ".repeat(row_label_width + 1));
+        for col in 0..num_columns {
+            let place = 10_usize.pow(digit as u32);
+            if digit == 0 || col % place == 0 && col >= place {
+                output.push(char::from(b'0' + ((col / place) % 10) as u8));
+            } else {
+                output.push(' ');
+            }
+        }
+        output.push('\n');
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Array2D<u8> {
+        Array2D::from_rows(&[b"#.#".to_vec(), b"...".to_vec()]).unwrap()
+    }
+
+    #[test]
+    fn test_render_array_plain() {
+        let rendered = render_array(&sample(), &RenderOptions::default());
+        assert_eq!(rendered, "#.#\n...\n");
+    }
+
+    #[test]
+    fn test_render_array_overlays() {
+        let options = RenderOptions {
+            overlays: vec![
+                Overlay::path(&[(1, 0), (1, 1), (0, 1)], None),
+                Overlay::marker(&[(1, 2)], b'E', None),
+            ],
+            ..Default::default()
+        };
+        assert_eq!(render_array(&sample(), &options), "#.#\n>^E\n");
+    }
+
+    #[test]
+    fn test_render_array_colour() {
+        let options = RenderOptions {
+            overlays: vec![Overlay::highlight(&[(0, 1)], AnsiColour::Red)],
+            value_colours: HashMap::from([(b'#', AnsiColour::Green)]),
+            use_colour: true,
+            ..Default::default()
+        };
+        let rendered = render_array(&sample(), &options);
+        let first_row = rendered.lines().next().unwrap();
+        assert_eq!(
+            first_row,
+            "\x1b[32m#\x1b[0m\x1b[41m.\x1b[0m\x1b[32m#\x1b[0m"
+        );
+        // Colour is ignored unless enabled
+        let options = RenderOptions {
+            use_colour: false,
+            ..options
+        };
+        assert_eq!(render_array(&sample(), &options), "#.#\n...\n");
+    }
+
+    #[test]
+    fn test_render_array_rulers() {
+        let grid = Array2D::filled_with(b'.', 11, 12);
+        let options = RenderOptions {
+            rulers: true,
+            ..Default::default()
+        };
+        let rendered = render_array(&grid, &options);
+        let lines: Vec<&str> = rendered.lines().collect();
+        assert_eq!(lines[0], "             1 ");
+        assert_eq!(lines[1], "   012345678901");
+        assert_eq!(lines[2], " 0 ............");
+        assert_eq!(lines[12], "10 ............");
+    }
+}
@@ -0,0 +1,207 @@
+use array2d::Array2D;
+
+/// One of the 8 symmetries of a rectangle (the D4 group), applied to a grid
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Orientation {
+    Identity,
+    Rotate90, // Clockwise
+    Rotate180,
+    Rotate270, // Clockwise, aka 90 counterclockwise
+    FlipHorizontal,
+    FlipVertical,
+    Transpose,     // Mirror over the main diagonal
+    AntiTranspose, // Mirror over the anti diagonal
+}
+
+impl Orientation {
+    pub fn all() -> [Orientation; 8] {
+        [
+            Orientation::Identity,
+            Orientation::Rotate90,
+            Orientation::Rotate180,
+            Orientation::Rotate270,
+            Orientation::FlipHorizontal,
+            Orientation::FlipVertical,
+            Orientation::Transpose,
+            Orientation::AntiTranspose,
+        ]
+    }
+    pub fn apply<T: Clone>(&self, grid: &Array2D<T>) -> Array2D<T> {
+        match self {
+            Orientation::Identity => grid.clone(),
+            Orientation::Rotate90 => rotate_array_90(grid),
+            Orientation::Rotate180 => rotate_array_180(grid),
+            Orientation::Rotate270 => rotate_array_270(grid),
+            Orientation::FlipHorizontal => flip_array_horizontal(grid),
+            Orientation::FlipVertical => flip_array_vertical(grid),
+            Orientation::Transpose => transpose_array(grid),
+            Orientation::AntiTranspose => anti_transpose_array(grid),
+        }
+    }
+}
+
+// Build a new grid of the given size, where each output cell is copied from the source cell returned by `source_of`
+fn remap_array<T, F>(
+    grid: &Array2D<T>,
+    num_rows: usize,
+    num_columns: usize,
+    source_of: F,
+) -> Array2D<T>
+where
+    T: Clone,
+    F: Fn(usize, usize) -> (usize, usize),
+{
+    let elements = (0..num_rows)
+        .flat_map(|row| (0..num_columns).map(move |col| (row, col)))
+        .map(|(row, col)| grid[source_of(row, col)].clone());
+    Array2D::from_iter_row_major(elements, num_rows, num_columns).unwrap()
+}
+
+/// Rotate 90 degrees clockwise, a R x C grid becomes C x R
+pub fn rotate_array_90<T: Clone>(grid: &Array2D<T>) -> Array2D<T> {
+    let rows = grid.num_rows();
+    remap_array(grid, grid.num_columns(), rows, |r, c| (rows - 1 - c, r))
+}
+
+pub fn rotate_array_180<T: Clone>(grid: &Array2D<T>) -> Array2D<T> {
+    let (rows, cols) = (grid.num_rows(), grid.num_columns());
+    remap_array(grid, rows, cols, |r, c| (rows - 1 - r, cols - 1 - c))
+}
+
+/// Rotate 270 degrees clockwise (90 counterclockwise), a R x C grid becomes C x R
+pub fn rotate_array_270<T: Clone>(grid: &Array2D<T>) -> Array2D<T> {
+    let cols = grid.num_columns();
+    remap_array(grid, cols, grid.num_rows(), |r, c| (c, cols - 1 - r))
+}
+
+/// Mirror left <-> right
+pub fn flip_array_horizontal<T: Clone>(grid: &Array2D<T>) -> Array2D<T> {
+    let (rows, cols) = (grid.num_rows(), grid.num_columns());
+    remap_array(grid, rows, cols, |r, c| (r, cols - 1 - c))
+}
+
+/// Mirror top <-> bottom
+pub fn flip_array_vertical<T: Clone>(grid: &Array2D<T>) -> Array2D<T> {
+    let (rows, cols) = (grid.num_rows(), grid.num_columns());
+    remap_array(grid, rows, cols, |r, c| (rows - 1 - r, c))
+}
+
+/// Swap rows and columns, a R x C grid becomes C x R
+pub fn transpose_array<T: Clone>(grid: &Array2D<T>) -> Array2D<T> {
+    remap_array(grid, grid.num_columns(), grid.num_rows(), |r, c| (c, r))
+}
+
+/// Mirror over the top-right to bottom-left diagonal, a R x C grid becomes C x R
+pub fn anti_transpose_array<T: Clone>(grid: &Array2D<T>) -> Array2D<T> {
+    let (rows, cols) = (grid.num_rows(), grid.num_columns());
+    remap_array(grid, cols, rows, |r, c| (rows - 1 - c, cols - 1 - r))
+}
+
+/// Iterate over every distinct orientation of the grid, along with the transform that produced it
+/// Symmetric grids yield fewer than 8 results, i.e. a square of one value only yields `Identity`
+pub fn unique_orientations<T>(grid: &Array2D<T>) -> impl Iterator<Item = (Orientation, Array2D<T>)>
+where
+    T: Clone + PartialEq,
+{
+    let mut seen: Vec<Array2D<T>> = Vec::with_capacity(8);
+    Orientation::all()
+        .into_iter()
+        .filter_map(move |orientation| {
+            let oriented = orientation.apply(grid);
+            if seen.contains(&oriented) {
+                None
+            } else {
+                seen.push(oriented.clone());
+                Some((orientation, oriented))
+            }
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 2 rows x 3 columns
+    // 1 2 3
+    // 4 5 6
+    fn sample() -> Array2D<u8> {
+        Array2D::from_row_major(&[1, 2, 3, 4, 5, 6], 2, 3).unwrap()
+    }
+
+    #[test]
+    fn test_rotate_array_90() {
+        let expected = Array2D::from_rows(&[vec![4, 1], vec![5, 2], vec![6, 3]]).unwrap();
+        assert_eq!(rotate_array_90(&sample()), expected);
+    }
+
+    #[test]
+    fn test_rotate_array_180() {
+        let expected = Array2D::from_rows(&[vec![6, 5, 4], vec![3, 2, 1]]).unwrap();
+        assert_eq!(rotate_array_180(&sample()), expected);
+    }
+
+    #[test]
+    fn test_rotate_array_270() {
+        let expected = Array2D::from_rows(&[vec![3, 6], vec![2, 5], vec![1, 4]]).unwrap();
+        assert_eq!(rotate_array_270(&sample()), expected);
+    }
+
+    #[test]
+    fn test_rotations_compose() {
+        let grid = sample();
+        assert_eq!(
+            rotate_array_90(&rotate_array_90(&grid)),
+            rotate_array_180(&grid)
+        );
+        assert_eq!(
+            rotate_array_90(&rotate_array_180(&grid)),
+            rotate_array_270(&grid)
+        );
+        assert_eq!(rotate_array_90(&rotate_array_270(&grid)), grid);
+    }
+
+    #[test]
+    fn test_flips() {
+        let horizontal = Array2D::from_rows(&[vec![3, 2, 1], vec![6, 5, 4]]).unwrap();
+        let vertical = Array2D::from_rows(&[vec![4, 5, 6], vec![1, 2, 3]]).unwrap();
+        assert_eq!(flip_array_horizontal(&sample()), horizontal);
+        assert_eq!(flip_array_vertical(&sample()), vertical);
+    }
+
+    #[test]
+    fn test_transposes() {
+        let transposed = Array2D::from_rows(&[vec![1, 4], vec![2, 5], vec![3, 6]]).unwrap();
+        let anti = Array2D::from_rows(&[vec![6, 3], vec![5, 2], vec![4, 1]]).unwrap();
+        assert_eq!(transpose_array(&sample()), transposed);
+        assert_eq!(anti_transpose_array(&sample()), anti);
+    }
+
+    #[test]
+    fn test_unique_orientations_asymmetric() {
+        let grid =
+            Array2D::from_rows(&[vec![b'#', b'#'], vec![b'#', b'.'], vec![b'#', b'.']]).unwrap();
+        let orientations: Vec<_> = unique_orientations(&grid).collect();
+        assert_eq!(orientations.len(), 8);
+        for (orientation, oriented) in orientations {
+            assert_eq!(orientation.apply(&grid), oriented);
+        }
+    }
+
+    #[test]
+    fn test_unique_orientations_symmetric() {
+        // A plus shape is the same in every orientation
+        let plus = Array2D::from_rows(&[
+            vec![b'.', b'#', b'.'],
+            vec![b'#', b'#', b'#'],
+            vec![b'.', b'#', b'.'],
+        ])
+        .unwrap();
+        let orientations: Vec<_> = unique_orientations(&plus).collect();
+        assert_eq!(orientations.len(), 1);
+        assert_eq!(orientations[0].0, Orientation::Identity);
+
+        // A straight line only has a horizontal and vertical form
+        let line = Array2D::from_rows(&[vec![1u8, 1, 1]]).unwrap();
+        assert_eq!(unique_orientations(&line).count(), 2);
+    }
+}
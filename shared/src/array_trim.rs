@@ -1,37 +1,66 @@
 use array2d::Array2D;
 
-pub fn trim_array_to_bounds(array: Array2D<u8>, bg_char: u8) -> Array2D<u8> {
+use crate::{ArrayPosition, GridBounds};
+
+/// Crop the array down to the smallest area containing everything that is not bg_char
+/// Returns the cropped array and the (row, col) in the original array that its top left came from,
+/// or None if the whole array is background
+pub fn trim_array_to_bounds<T>(array: Array2D<T>, bg_char: T) -> Option<(Array2D<T>, ArrayPosition)>
+where
+    T: PartialEq + Clone,
+{
     // Find the lowest row,col and highest row,col that is not bg_char
-    let min_row = (0..array.num_rows())
-        .find(|row| (0..array.num_columns()).any(|col| array.get(*row, col).unwrap() != &bg_char))
-        .unwrap();
+    let mut bounds: Option<GridBounds> = None;
+    for (pos, value) in array.enumerate_row_major() {
+        if *value != bg_char {
+            match bounds.as_mut() {
+                Some(bounds) => bounds.include(pos),
+                None => bounds = Some(GridBounds::new(pos)),
+            }
+        }
+    }
+    let bounds = bounds?;
 
-    let max_row = (0..array.num_rows())
-        .rev()
-        .find(|row| (0..array.num_columns()).any(|col| array.get(*row, col).unwrap() != &bg_char))
-        .unwrap();
-    let min_col = (0..array.num_columns())
-        .find(|col| (0..array.num_rows()).any(|row| array.get(row, *col).unwrap() != &bg_char))
-        .unwrap();
-    let max_col = (0..array.num_columns())
-        .rev()
-        .find(|col| (0..array.num_rows()).any(|row| array.get(row, *col).unwrap() != &bg_char))
-        .unwrap();
     // Copy out the region from min<->max
-    println!(
-        " min_row: {min_row},    max_row: {max_row},    min_col: {min_col},    max_col: {max_col}"
-    );
-    let new_elements: Vec<u8> = array
+    let new_elements = array
         .enumerate_row_major()
-        .filter_map(|((row, col), val)| {
-            if row >= min_row && row <= max_row && col >= min_col && col <= max_col {
-                Some(*val)
-            } else {
-                None
-            }
-        })
-        .collect();
-    Array2D::from_row_major(&new_elements, max_row - min_row + 1, max_col - min_col + 1).unwrap()
+        .filter(|(pos, _)| bounds.contains(*pos))
+        .map(|(_, value)| value.clone());
+    let trimmed =
+        Array2D::from_iter_row_major(new_elements, bounds.num_rows(), bounds.num_columns())
+            .unwrap();
+    Some((trimmed, bounds.min))
+}
+
+/// Add `padding` cells of fill on every side of the array
+pub fn pad_array<T: Clone>(array: &Array2D<T>, padding: usize, fill: T) -> Array2D<T> {
+    expand_array(
+        array,
+        array.num_rows() + 2 * padding,
+        array.num_columns() + 2 * padding,
+        (padding, padding),
+        fill,
+    )
+}
+
+/// Place the array into a new larger array of fill, with its top left corner at offset
+/// Panics if the array doesn't fit
+pub fn expand_array<T: Clone>(
+    array: &Array2D<T>,
+    num_rows: usize,
+    num_columns: usize,
+    offset: ArrayPosition,
+    fill: T,
+) -> Array2D<T> {
+    assert!(
+        offset.0 + array.num_rows() <= num_rows && offset.1 + array.num_columns() <= num_columns,
+        "Array does not fit at offset {offset:?}"
+    );
+    let mut expanded = Array2D::filled_with(fill, num_rows, num_columns);
+    for ((row, col), value) in array.enumerate_row_major() {
+        expanded[(row + offset.0, col + offset.1)] = value.clone();
+    }
+    expanded
 }
 
 #[cfg(test)]
@@ -58,7 +87,7 @@ mod tests {
         )
         .unwrap();
 
-        let res = trim_array_to_bounds(grid, b'.');
+        let (res, offset) = trim_array_to_bounds(grid, b'.').unwrap();
         let expected = Array2D::from_row_major(
             &vec![
                 b'O', b'O', b'.', b'.', b'.', b'.', b'O', b'O', b'O', //
@@ -75,5 +104,38 @@ mod tests {
         )
         .unwrap();
         assert_eq!(res, expected);
+        assert_eq!(offset, (1, 1));
+    }
+
+    #[test]
+    fn test_trim_array_to_bounds_all_background() {
+        let grid = Array2D::filled_with(b'.', 3, 4);
+        assert_eq!(trim_array_to_bounds(grid, b'.'), None);
+    }
+
+    #[test]
+    fn test_trim_array_to_bounds_generic() {
+        let mut grid = Array2D::filled_with(false, 4, 5);
+        grid[(2, 3)] = true;
+        let (res, offset) = trim_array_to_bounds(grid, false).unwrap();
+        assert_eq!(res, Array2D::filled_with(true, 1, 1));
+        assert_eq!(offset, (2, 3));
+    }
+
+    #[test]
+    fn test_pad_and_trim_round_trip() {
+        let grid = Array2D::from_rows(&[vec![1, 0, 2], vec![0, 3, 0]]).unwrap();
+        let padded = pad_array(&grid, 2, 0);
+        assert_eq!((padded.num_rows(), padded.num_columns()), (6, 7));
+        assert_eq!(padded[(2, 2)], 1);
+        assert_eq!(padded[(3, 3)], 3);
+        assert_eq!(trim_array_to_bounds(padded, 0), Some((grid, (2, 2))));
+    }
+
+    #[test]
+    fn test_expand_array() {
+        let grid = Array2D::filled_with(b'#', 1, 2);
+        let expanded = expand_array(&grid, 2, 4, (1, 2), b'.');
+        assert_eq!(expanded.as_rows(), vec![b"....".to_vec(), b"..##".to_vec()]);
     }
 }
@@ -0,0 +1,214 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use array2d::Array2D;
+
+use crate::{Adjacents, adjacent_positions_iter};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UpdateMode {
+    /// Every cell sees the grid as it was at the start of the step
+    Synchronous,
+    /// Cells are updated in row major order, so later cells see the earlier cells' new values
+    InPlace,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AutomatonOutcome {
+    /// A step made no changes
+    FixedPoint,
+    /// The grid returned to the state it was in after `start` steps, and repeats every `length` steps
+    Cycle { start: usize, length: usize },
+    /// Ran out of steps before settling
+    StepLimit,
+}
+
+/// Summary of `run_automaton`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AutomatonRun {
+    /// Number of steps that changed the grid
+    pub steps: usize,
+    pub outcome: AutomatonOutcome,
+    /// Number of cells changed by each step
+    pub changes_per_step: Vec<usize>,
+    pub total_changes: usize,
+}
+
+/// Apply the rule to every cell once, returning how many cells changed
+/// The rule is given the cell and its in-bounds neighbours, and returns the new value for the cell
+pub fn step_automaton<T, F>(
+    grid: &mut Array2D<T>,
+    neighbourhood: Adjacents,
+    mode: UpdateMode,
+    rule: F,
+) -> usize
+where
+    T: Clone + PartialEq,
+    F: Fn(&T, &[T]) -> T,
+{
+    let snapshot = match mode {
+        UpdateMode::Synchronous => Some(grid.clone()),
+        UpdateMode::InPlace => None,
+    };
+    // Reused for every cell so we don't allocate per cell
+    let mut neighbours = Vec::with_capacity(neighbourhood.get_pattern().len());
+    let mut changes = 0;
+    for row in 0..grid.num_rows() {
+        for col in 0..grid.num_columns() {
+            let source = snapshot.as_ref().unwrap_or(grid);
+            neighbours.clear();
+            neighbours.extend(
+                adjacent_positions_iter(source, (row, col), neighbourhood)
+                    .map(|pos| source[pos].clone()),
+            );
+            let new_value = rule(&source[(row, col)], &neighbours);
+            if new_value != grid[(row, col)] {
+                grid[(row, col)] = new_value;
+                changes += 1;
+            }
+        }
+    }
+    changes
+}
+
+/// Keep stepping until the grid stops changing, repeats a previous state, or max_steps is reached
+/// Every state is kept to spot repeats, so the grid is left at the first repeated state
+pub fn run_automaton<T, F>(
+    grid: &mut Array2D<T>,
+    neighbourhood: Adjacents,
+    mode: UpdateMode,
+    max_steps: usize,
+    rule: F,
+) -> AutomatonRun
+where
+    T: Clone + Eq + Hash,
+    F: Fn(&T, &[T]) -> T,
+{
+    let mut seen_states = HashMap::from([(grid.clone(), 0)]);
+    let mut changes_per_step = Vec::new();
+    let mut outcome = AutomatonOutcome::StepLimit;
+
+    while changes_per_step.len() < max_steps {
+        let changes = step_automaton(grid, neighbourhood, mode, &rule);
+        if changes == 0 {
+            outcome = AutomatonOutcome::FixedPoint;
+            break;
+        }
+        changes_per_step.push(changes);
+        let step = changes_per_step.len();
+        if let Some(start) = seen_states.insert(grid.clone(), step) {
+            outcome = AutomatonOutcome::Cycle {
+                start,
+                length: step - start,
+            };
+            break;
+        }
+    }
+
+    AutomatonRun {
+        steps: changes_per_step.len(),
+        outcome,
+        total_changes: changes_per_step.iter().sum(),
+        changes_per_step,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::array2d::grid_from_lines;
+
+    // Conway's game of life
+    fn life(cell: &u8, neighbours: &[u8]) -> u8 {
+        let alive = neighbours.iter().filter(|n| **n == b'#').count();
+        match (*cell, alive) {
+            (b'#', 2 | 3) | (b'.', 3) => b'#',
+            _ => b'.',
+        }
+    }
+
+    // Anything next to a # becomes a #
+    fn spread(cell: &u8, neighbours: &[u8]) -> u8 {
+        if neighbours.contains(&b'#') {
+            b'#'
+        } else {
+            *cell
+        }
+    }
+
+    #[test]
+    fn test_step_automaton_blinker() {
+        let mut grid = grid_from_lines(&[".....", "..#..", "..#..", "..#..", "....."]);
+        let changes = step_automaton(&mut grid, Adjacents::ALL, UpdateMode::Synchronous, life);
+        assert_eq!(changes, 4);
+        assert_eq!(
+            grid,
+            grid_from_lines(&[".....", ".....", ".###.", ".....", "....."])
+        );
+    }
+
+    #[test]
+    fn test_step_automaton_in_place() {
+        // In place the spread runs all the way along the row in one step
+        let mut grid = grid_from_lines(&["#...."]);
+        step_automaton(
+            &mut grid,
+            Adjacents::HORIZONTAL,
+            UpdateMode::Synchronous,
+            spread,
+        );
+        assert_eq!(grid, grid_from_lines(&["##..."]));
+
+        let mut grid = grid_from_lines(&["#...."]);
+        let changes = step_automaton(
+            &mut grid,
+            Adjacents::HORIZONTAL,
+            UpdateMode::InPlace,
+            spread,
+        );
+        assert_eq!(changes, 4);
+        assert_eq!(grid, grid_from_lines(&["#####"]));
+    }
+
+    #[test]
+    fn test_run_automaton_fixed_point() {
+        let mut grid = grid_from_lines(&["#...."]);
+        let run = run_automaton(
+            &mut grid,
+            Adjacents::HORIZONTAL,
+            UpdateMode::Synchronous,
+            100,
+            spread,
+        );
+        assert_eq!(run.outcome, AutomatonOutcome::FixedPoint);
+        assert_eq!(run.steps, 4);
+        assert_eq!(run.changes_per_step, vec![1, 1, 1, 1]);
+        assert_eq!(run.total_changes, 4);
+        assert_eq!(grid, grid_from_lines(&["#####"]));
+    }
+
+    #[test]
+    fn test_run_automaton_cycle() {
+        let mut grid = grid_from_lines(&[".....", "..#..", "..#..", "..#..", "....."]);
+        let run = run_automaton(
+            &mut grid,
+            Adjacents::ALL,
+            UpdateMode::Synchronous,
+            100,
+            life,
+        );
+        assert_eq!(
+            run.outcome,
+            AutomatonOutcome::Cycle {
+                start: 0,
+                length: 2
+            }
+        );
+        assert_eq!(run.steps, 2);
+
+        let mut grid = grid_from_lines(&[".....", "..#..", "..#..", "..#..", "....."]);
+        let run = run_automaton(&mut grid, Adjacents::ALL, UpdateMode::Synchronous, 1, life);
+        assert_eq!(run.outcome, AutomatonOutcome::StepLimit);
+        assert_eq!(run.steps, 1);
+    }
+}
@@ -0,0 +1,320 @@
+use array2d::Array2D;
+
+use crate::{Adjacents, ArrayPosition};
+
+/// Boolean grid packed 64 cells to a `u64`, so whole rows can be shifted and combined a word at a time
+/// Bit `col % 64` of word `col / 64` in each row holds that column
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BitGrid {
+    num_rows: usize,
+    num_columns: usize,
+    words_per_row: usize,
+    words: Vec<u64>,
+}
+
+impl BitGrid {
+    pub fn new(num_rows: usize, num_columns: usize) -> Self {
+        let words_per_row = num_columns.div_ceil(64);
+        BitGrid {
+            num_rows,
+            num_columns,
+            words_per_row,
+            words: vec![0; num_rows * words_per_row],
+        }
+    }
+    /// Set every cell that holds the marker
+    pub fn from_array(array: &Array2D<u8>, marker: u8) -> Self {
+        let mut grid = BitGrid::new(array.num_rows(), array.num_columns());
+        for (pos, value) in array.enumerate_row_major() {
+            if *value == marker {
+                grid.set(pos, true);
+            }
+        }
+        grid
+    }
+    pub fn to_array(&self, set: u8, unset: u8) -> Array2D<u8> {
+        let elements = (0..self.num_rows)
+            .flat_map(|row| (0..self.num_columns).map(move |col| (row, col)))
+            .map(|pos| if self.get(pos) { set } else { unset });
+        Array2D::from_iter_row_major(elements, self.num_rows, self.num_columns).unwrap()
+    }
+
+    pub fn num_rows(&self) -> usize {
+        self.num_rows
+    }
+    pub fn num_columns(&self) -> usize {
+        self.num_columns
+    }
+
+    fn row_words(&self, row: usize) -> &[u64] {
+        &self.words[row * self.words_per_row..(row + 1) * self.words_per_row]
+    }
+
+    pub fn get(&self, pos: ArrayPosition) -> bool {
+        assert!(pos.0 < self.num_rows && pos.1 < self.num_columns);
+        self.row_words(pos.0)[pos.1 / 64] >> (pos.1 % 64) & 1 == 1
+    }
+    pub fn set(&mut self, pos: ArrayPosition, value: bool) {
+        assert!(pos.0 < self.num_rows && pos.1 < self.num_columns);
+        let word = &mut self.words[pos.0 * self.words_per_row + pos.1 / 64];
+        if value {
+            *word |= 1 << (pos.1 % 64);
+        } else {
+            *word &= !(1 << (pos.1 % 64));
+        }
+    }
+
+    /// Number of set cells
+    pub fn count_ones(&self) -> usize {
+        self.words
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+    /// Positions of the set cells in row major order
+    pub fn iter_ones(&self) -> impl Iterator<Item = ArrayPosition> + '_ {
+        (0..self.num_rows)
+            .flat_map(move |row| (0..self.num_columns).map(move |col| (row, col)))
+            .filter(|pos| self.get(*pos))
+    }
+
+    fn zip_words<F>(&self, other: &BitGrid, f: F) -> BitGrid
+    where
+        F: Fn(u64, u64) -> u64,
+    {
+        assert_eq!(
+            (self.num_rows, self.num_columns),
+            (other.num_rows, other.num_columns)
+        );
+        let mut result = self.clone();
+        for (word, other_word) in result.words.iter_mut().zip(&other.words) {
+            *word = f(*word, *other_word);
+        }
+        result.clear_padding();
+        result
+    }
+    pub fn and(&self, other: &BitGrid) -> BitGrid {
+        self.zip_words(other, |a, b| a & b)
+    }
+    pub fn or(&self, other: &BitGrid) -> BitGrid {
+        self.zip_words(other, |a, b| a | b)
+    }
+    pub fn xor(&self, other: &BitGrid) -> BitGrid {
+        self.zip_words(other, |a, b| a ^ b)
+    }
+    /// Cells set in self but not in other
+    pub fn and_not(&self, other: &BitGrid) -> BitGrid {
+        self.zip_words(other, |a, b| a & !b)
+    }
+    pub fn not(&self) -> BitGrid {
+        self.zip_words(self, |a, _| !a)
+    }
+
+    // Bits past the last column in each row must stay clear so counts and comparisons are right
+    fn clear_padding(&mut self) {
+        let spare_bits = self.words_per_row * 64 - self.num_columns;
+        if spare_bits == 0 || self.words_per_row == 0 {
+            return;
+        }
+        let mask = u64::MAX >> spare_bits;
+        for row in 0..self.num_rows {
+            self.words[(row + 1) * self.words_per_row - 1] &= mask;
+        }
+    }
+
+    /// Move every cell by (rows, columns), cells moved off the edge are lost and the gaps are cleared
+    pub fn shift(&self, rows: i64, columns: i64) -> BitGrid {
+        let mut result = BitGrid::new(self.num_rows, self.num_columns);
+        let word_shift = columns.unsigned_abs() as usize / 64;
+        let bit_shift = columns.unsigned_abs() as u32 % 64;
+        for row in 0..self.num_rows {
+            let source_row = row as i64 - rows;
+            if source_row < 0 || source_row >= self.num_rows as i64 {
+                continue;
+            }
+            let source = self.row_words(source_row as usize);
+            let target =
+                &mut result.words[row * self.words_per_row..(row + 1) * self.words_per_row];
+            for (index, word) in target.iter_mut().enumerate() {
+                // Moving to higher columns moves bits towards the most significant end
+                let (low, high) = if columns >= 0 {
+                    let high = index.checked_sub(word_shift).map(|i| source[i]);
+                    let low = index.checked_sub(word_shift + 1).map(|i| source[i]);
+                    (low, high)
+                } else {
+                    let low = source.get(index + word_shift).copied();
+                    let high = source.get(index + word_shift + 1).copied();
+                    (low, high)
+                };
+                let (low, high) = (low.unwrap_or(0), high.unwrap_or(0));
+                *word = match (columns >= 0, bit_shift) {
+                    (true, 0) => high,
+                    (true, bits) => high << bits | low >> (64 - bits),
+                    (false, 0) => low,
+                    (false, bits) => low >> bits | high << (64 - bits),
+                };
+            }
+        }
+        result.clear_padding();
+        result
+    }
+
+    // Bit-sliced counters, plane n holds bit n of the number of set neighbours for every cell
+    fn neighbour_count_planes(&self, adjacents: Adjacents) -> [BitGrid; 4] {
+        let mut planes = std::array::from_fn(|_| BitGrid::new(self.num_rows, self.num_columns));
+        for (dr, dc) in adjacents.get_pattern() {
+            // Line up the neighbour at (+dr, +dc) with each cell, then ripple carry add it in
+            let mut carry = self.shift(-dr, -dc).words;
+            for plane in planes.iter_mut() {
+                for (word, carry) in plane.words.iter_mut().zip(carry.iter_mut()) {
+                    let sum = *word ^ *carry;
+                    *carry &= *word;
+                    *word = sum;
+                }
+            }
+        }
+        planes
+    }
+
+    /// Number of set neighbours for every cell
+    pub fn neighbour_counts(&self, adjacents: Adjacents) -> Array2D<u8> {
+        let planes = self.neighbour_count_planes(adjacents);
+        let elements = (0..self.num_rows)
+            .flat_map(|row| (0..self.num_columns).map(move |col| (row, col)))
+            .map(|pos| {
+                planes
+                    .iter()
+                    .enumerate()
+                    .map(|(bit, plane)| (plane.get(pos) as u8) << bit)
+                    .sum()
+            });
+        Array2D::from_iter_row_major(elements, self.num_rows, self.num_columns).unwrap()
+    }
+
+    /// Cells (set or not) that have fewer than `limit` set neighbours, compared a word at a time
+    pub fn neighbour_count_below(&self, adjacents: Adjacents, limit: u8) -> BitGrid {
+        if limit > 15 {
+            return BitGrid::new(self.num_rows, self.num_columns).not();
+        }
+        let planes = self.neighbour_count_planes(adjacents);
+        let mut result = BitGrid::new(self.num_rows, self.num_columns);
+        for index in 0..self.words.len() {
+            // Compare from the most significant bit down, tracking lanes that are still equal
+            let mut less = 0;
+            let mut equal = u64::MAX;
+            for (bit, plane) in planes.iter().enumerate().rev() {
+                let count_bit = plane.words[index];
+                if limit >> bit & 1 == 1 {
+                    less |= equal & !count_bit;
+                    equal &= count_bit;
+                } else {
+                    equal &= !count_bit;
+                }
+            }
+            result.words[index] = less;
+        }
+        result.clear_padding();
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::array2d::grid_from_lines;
+
+    #[test]
+    fn test_bit_grid_round_trip() {
+        let array = grid_from_lines(&["#..#", ".##.", "...#"]);
+        let grid = BitGrid::from_array(&array, b'#');
+        assert_eq!(grid.count_ones(), 5);
+        assert!(grid.get((1, 2)));
+        assert!(!grid.get((2, 2)));
+        assert_eq!(grid.to_array(b'#', b'.'), array);
+        assert_eq!(
+            grid.iter_ones().collect::<Vec<_>>(),
+            vec![(0, 0), (0, 3), (1, 1), (1, 2), (2, 3)]
+        );
+    }
+
+    #[test]
+    fn test_bit_grid_logic() {
+        let a = BitGrid::from_array(&grid_from_lines(&["##..", "##.."]), b'#');
+        let b = BitGrid::from_array(&grid_from_lines(&[".##.", ".##."]), b'#');
+        assert_eq!(a.and(&b).count_ones(), 2);
+        assert_eq!(a.or(&b).count_ones(), 6);
+        assert_eq!(a.xor(&b).count_ones(), 4);
+        assert_eq!(a.and_not(&b).count_ones(), 2);
+        assert_eq!(
+            a.not().to_array(b'#', b'.'),
+            grid_from_lines(&["..##", "..##"])
+        );
+    }
+
+    #[test]
+    fn test_bit_grid_shift_across_words() {
+        // Wide enough to span 3 words per row
+        let mut grid = BitGrid::new(3, 150);
+        grid.set((1, 0), true);
+        grid.set((1, 63), true);
+        grid.set((1, 64), true);
+        grid.set((1, 149), true);
+
+        let right = grid.shift(1, 1);
+        assert_eq!(
+            right.iter_ones().collect::<Vec<_>>(),
+            vec![(2, 1), (2, 64), (2, 65)]
+        );
+
+        let left = grid.shift(-1, -64);
+        assert_eq!(left.iter_ones().collect::<Vec<_>>(), vec![(0, 0), (0, 85)]);
+
+        let far_right = grid.shift(0, 100);
+        assert_eq!(far_right.iter_ones().collect::<Vec<_>>(), vec![(1, 100)]);
+    }
+
+    #[test]
+    fn test_bit_grid_neighbour_counts() {
+        let array = grid_from_lines(&["###", "#.#", "###"]);
+        let grid = BitGrid::from_array(&array, b'#');
+        let counts = grid.neighbour_counts(Adjacents::ALL);
+        assert_eq!(
+            counts.as_rows(),
+            vec![vec![2, 4, 2], vec![4, 8, 4], vec![2, 4, 2]]
+        );
+        let counts = grid.neighbour_counts(Adjacents::CROSS);
+        assert_eq!(
+            counts.as_rows(),
+            vec![vec![2, 2, 2], vec![2, 4, 2], vec![2, 2, 2]]
+        );
+    }
+
+    #[test]
+    fn test_bit_grid_neighbour_count_below() {
+        // Rolls of paper that can be reached by a forklift have fewer than 4 neighbouring rolls
+        let array = grid_from_lines(&[
+            "..@@.@@@@.",
+            "@@@.@.@.@@",
+            "@@@@@.@.@@",
+            "@.@@@@..@.",
+            "@@.@@@@.@@",
+            ".@@@@@@@.@",
+            ".@.@.@.@@@",
+            "@.@@@.@@@@",
+            ".@@@@@@@@.",
+            "@.@.@@@.@.",
+        ]);
+        let rolls = BitGrid::from_array(&array, b'@');
+        let accessible = rolls.and(&rolls.neighbour_count_below(Adjacents::ALL, 4));
+        assert_eq!(accessible.count_ones(), 13);
+
+        // Matches counting each cell the slow way
+        let counts = rolls.neighbour_counts(Adjacents::ALL);
+        for limit in 0..10 {
+            let below = rolls.neighbour_count_below(Adjacents::ALL, limit);
+            for (pos, count) in counts.enumerate_row_major() {
+                assert_eq!(below.get(pos), *count < limit);
+            }
+        }
+    }
+}
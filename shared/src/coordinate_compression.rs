@@ -0,0 +1,158 @@
+use array2d::Array2D;
+
+use crate::ArrayPosition;
+
+/// Maps a sparse set of values on one axis down to consecutive cell indices
+/// Every distinct value gets its own cell, and any run of values between two of them is squashed
+/// into a single gap cell, so shapes keep the same topology after compressing
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompressedAxis {
+    // (first real value, number of real values) for each cell, in order
+    cells: Vec<(i64, i64)>,
+}
+
+impl CompressedAxis {
+    pub fn new<I: IntoIterator<Item = i64>>(values: I) -> Self {
+        let mut values: Vec<i64> = values.into_iter().collect();
+        values.sort_unstable();
+        values.dedup();
+        let mut cells = Vec::with_capacity(values.len() * 2);
+        for (index, value) in values.iter().enumerate() {
+            cells.push((*value, 1));
+            if let Some(next) = values.get(index + 1)
+                && next - value > 1
+            {
+                cells.push((value + 1, next - value - 1));
+            }
+        }
+        CompressedAxis { cells }
+    }
+
+    /// Number of cells, including gap cells
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    /// The cell holding this real value, None if it is outside the values the axis was built from
+    pub fn index_of(&self, value: i64) -> Option<usize> {
+        let index = self.cells.partition_point(|(start, _)| *start <= value);
+        let (start, width) = self.cells.get(index.checked_sub(1)?)?;
+        (value < start + width).then_some(index - 1)
+    }
+    /// First real value in the cell
+    pub fn cell_start(&self, index: usize) -> i64 {
+        self.cells[index].0
+    }
+    /// Number of real values the cell stands for, 1 for every cell except gaps
+    pub fn cell_width(&self, index: usize) -> i64 {
+        self.cells[index].1
+    }
+    /// Number of real values covered by the cells from..=to
+    pub fn real_length(&self, from: usize, to: usize) -> i64 {
+        let (from, to) = (from.min(to), from.max(to));
+        self.cells[from..=to].iter().map(|(_, width)| width).sum()
+    }
+}
+
+/// Compression of both axes of a set of (row, col) points
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CoordinateCompression {
+    pub rows: CompressedAxis,
+    pub columns: CompressedAxis,
+}
+
+impl CoordinateCompression {
+    pub fn from_points(points: &[(i64, i64)]) -> Self {
+        CoordinateCompression {
+            rows: CompressedAxis::new(points.iter().map(|p| p.0)),
+            columns: CompressedAxis::new(points.iter().map(|p| p.1)),
+        }
+    }
+
+    /// Grid with one cell per compressed (row, col)
+    pub fn filled_grid<T: Clone>(&self, fill: T) -> Array2D<T> {
+        Array2D::filled_with(fill, self.rows.len(), self.columns.len())
+    }
+    pub fn compress_point(&self, point: (i64, i64)) -> Option<ArrayPosition> {
+        Some((
+            self.rows.index_of(point.0)?,
+            self.columns.index_of(point.1)?,
+        ))
+    }
+    /// Compress every point, panics if one is outside the compressed area
+    pub fn compress_points(&self, points: &[(i64, i64)]) -> Vec<ArrayPosition> {
+        points
+            .iter()
+            .map(|point| self.compress_point(*point).unwrap())
+            .collect()
+    }
+    /// The real (row, col) at the top left of a compressed cell
+    pub fn real_point(&self, pos: ArrayPosition) -> (i64, i64) {
+        (self.rows.cell_start(pos.0), self.columns.cell_start(pos.1))
+    }
+    /// Number of real cells a compressed cell stands for
+    pub fn cell_area(&self, pos: ArrayPosition) -> i64 {
+        self.rows.cell_width(pos.0) * self.columns.cell_width(pos.1)
+    }
+    /// Number of real cells in the rectangle of compressed cells with corners a and b (inclusive)
+    pub fn real_area(&self, a: ArrayPosition, b: ArrayPosition) -> i64 {
+        self.rows.real_length(a.0, b.0) * self.columns.real_length(a.1, b.1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compressed_axis() {
+        let axis = CompressedAxis::new([10, 3, 4, 10, 100]);
+        // 3, 4, gap 5..=9, 10, gap 11..=99, 100
+        assert_eq!(axis.len(), 6);
+        assert_eq!(axis.index_of(3), Some(0));
+        assert_eq!(axis.index_of(4), Some(1));
+        assert_eq!(axis.index_of(7), Some(2));
+        assert_eq!(axis.index_of(10), Some(3));
+        assert_eq!(axis.index_of(50), Some(4));
+        assert_eq!(axis.index_of(100), Some(5));
+        assert_eq!(axis.index_of(2), None);
+        assert_eq!(axis.index_of(101), None);
+        assert_eq!(axis.cell_width(2), 5);
+        assert_eq!(axis.cell_start(4), 11);
+        assert_eq!(axis.real_length(0, 5), 98);
+        assert_eq!(axis.real_length(3, 1), 7);
+        assert!(CompressedAxis::new([]).is_empty());
+    }
+
+    #[test]
+    fn test_coordinate_compression() {
+        let points = [
+            (1, 7),
+            (1, 11),
+            (7, 11),
+            (7, 9),
+            (5, 9),
+            (5, 2),
+            (3, 2),
+            (3, 7),
+        ];
+        let compression = CoordinateCompression::from_points(&points);
+        // Rows 1..=7, every gap between the used rows is a single row
+        assert_eq!(compression.rows.len(), 7);
+        // Columns 2, 3..=6, 7, 8, 9, 10, 11
+        assert_eq!(compression.columns.len(), 7);
+        let grid = compression.filled_grid(b'.');
+        assert_eq!((grid.num_rows(), grid.num_columns()), (7, 7));
+
+        let compressed = compression.compress_points(&points);
+        assert_eq!(compressed[0], (0, 2));
+        assert_eq!(compressed[5], (4, 0));
+        assert_eq!(compression.real_point((4, 1)), (5, 3));
+        assert_eq!(compression.cell_area((4, 1)), 4);
+        // Whole bounding box is rows 1..=7, cols 2..=11
+        assert_eq!(compression.real_area((0, 0), (6, 6)), 70);
+    }
+}
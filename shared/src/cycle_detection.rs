@@ -0,0 +1,231 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// A sequence of states that settles into a loop
+/// The state after `start` steps is the first one that repeats, and it comes back every `length` steps
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cycle {
+    pub start: usize,
+    pub length: usize,
+}
+
+impl Cycle {
+    /// The earliest step with the same state as step n
+    pub fn project(&self, n: usize) -> usize {
+        if n < self.start {
+            n
+        } else {
+            self.start + (n - self.start) % self.length
+        }
+    }
+}
+
+/// Floyd's tortoise and hare, uses constant memory but calls step roughly 3x more than needed
+/// Never returns if the sequence doesn't cycle
+pub fn floyd_cycle<S, F>(initial: &S, step: F) -> Cycle
+where
+    S: Clone + PartialEq,
+    F: Fn(&S) -> S,
+{
+    // Hare moves twice as fast, they meet somewhere inside the loop
+    let mut tortoise = step(initial);
+    let mut hare = step(&step(initial));
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&step(&hare));
+    }
+
+    // Restart the tortoise, moving both at the same speed they meet at the start of the loop
+    let mut start = 0;
+    tortoise = initial.clone();
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        start += 1;
+    }
+
+    let mut length = 1;
+    hare = step(&tortoise);
+    while tortoise != hare {
+        hare = step(&hare);
+        length += 1;
+    }
+    Cycle { start, length }
+}
+
+/// Brent's algorithm, uses constant memory and fewer steps than Floyd's
+/// Never returns if the sequence doesn't cycle
+pub fn brent_cycle<S, F>(initial: &S, step: F) -> Cycle
+where
+    S: Clone + PartialEq,
+    F: Fn(&S) -> S,
+{
+    // Find the loop length by teleporting the tortoise forward at each power of two
+    let mut power = 1;
+    let mut length = 1;
+    let mut tortoise = initial.clone();
+    let mut hare = step(initial);
+    while tortoise != hare {
+        if power == length {
+            tortoise = hare.clone();
+            power *= 2;
+            length = 0;
+        }
+        hare = step(&hare);
+        length += 1;
+    }
+
+    // Then walk two cursors `length` apart from the start until they meet
+    let mut tortoise = initial.clone();
+    let mut hare = initial.clone();
+    for _ in 0..length {
+        hare = step(&hare);
+    }
+    let mut start = 0;
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        start += 1;
+    }
+    Cycle { start, length }
+}
+
+/// Result of `find_cycle_hashed`, holding every state up to the end of the first loop
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CycleHistory<S> {
+    pub cycle: Cycle,
+    pub states: Vec<S>,
+}
+
+impl<S> CycleHistory<S> {
+    /// The state after n steps, for any n
+    pub fn state_at(&self, n: usize) -> &S {
+        &self.states[self.cycle.project(n)]
+    }
+}
+
+/// Step the state forward, remembering every state seen until one repeats
+/// Uses more memory than `brent_cycle` but steps each state only once and keeps the history
+/// Returns None if no repeat is found within max_steps
+pub fn find_cycle_hashed<S, F>(initial: S, step: F, max_steps: usize) -> Option<CycleHistory<S>>
+where
+    S: Clone + Eq + Hash,
+    F: FnMut(&S) -> S,
+{
+    let (states, cycle) = walk_until_repeat(initial, step, max_steps);
+    cycle.map(|cycle| CycleHistory { cycle, states })
+}
+
+/// The state after n steps, using cycle detection to skip ahead when n is huge (i.e. a billion steps)
+pub fn state_at_step<S, F>(initial: S, step: F, n: usize) -> S
+where
+    S: Clone + Eq + Hash,
+    F: FnMut(&S) -> S,
+{
+    let (mut states, cycle) = walk_until_repeat(initial, step, n);
+    match cycle {
+        Some(cycle) => states.swap_remove(cycle.project(n)),
+        // No repeat within n steps, so we walked all the way to step n
+        None => states.pop().unwrap(),
+    }
+}
+
+// Returns all states seen (without the repeat), and the cycle if one was found within max_steps
+fn walk_until_repeat<S, F>(initial: S, mut step: F, max_steps: usize) -> (Vec<S>, Option<Cycle>)
+where
+    S: Clone + Eq + Hash,
+    F: FnMut(&S) -> S,
+{
+    let mut seen = HashMap::from([(initial.clone(), 0)]);
+    let mut states = vec![initial];
+    while states.len() <= max_steps {
+        let next = step(states.last().unwrap());
+        if let Some(start) = seen.get(&next) {
+            let cycle = Cycle {
+                start: *start,
+                length: states.len() - start,
+            };
+            return (states, Some(cycle));
+        }
+        seen.insert(next.clone(), states.len());
+        states.push(next);
+    }
+    (states, None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 0 -> 1 -> 2 -> 3 -> 4 -> 5 -> 6 -> 3 ...
+    fn rho(state: &u32) -> u32 {
+        if *state == 6 { 3 } else { state + 1 }
+    }
+    const RHO_CYCLE: Cycle = Cycle {
+        start: 3,
+        length: 4,
+    };
+
+    #[test]
+    fn test_floyd_cycle() {
+        assert_eq!(floyd_cycle(&0, rho), RHO_CYCLE);
+        assert_eq!(
+            floyd_cycle(&5, rho),
+            Cycle {
+                start: 0,
+                length: 4
+            }
+        );
+    }
+
+    #[test]
+    fn test_brent_cycle() {
+        assert_eq!(brent_cycle(&0, rho), RHO_CYCLE);
+        assert_eq!(
+            brent_cycle(&5, rho),
+            Cycle {
+                start: 0,
+                length: 4
+            }
+        );
+    }
+
+    #[test]
+    fn test_algorithms_agree() {
+        // Pseudo random sequence in a small space
+        let step = |x: &u64| (x * x + 1) % 1009;
+        for initial in [0, 2, 17, 500] {
+            let floyd = floyd_cycle(&initial, step);
+            let brent = brent_cycle(&initial, step);
+            let hashed = find_cycle_hashed(initial, step, 10_000).unwrap();
+            assert_eq!(floyd, brent);
+            assert_eq!(floyd, hashed.cycle);
+        }
+    }
+
+    #[test]
+    fn test_find_cycle_hashed() {
+        let history = find_cycle_hashed(0, rho, 100).unwrap();
+        assert_eq!(history.cycle, RHO_CYCLE);
+        assert_eq!(history.states, vec![0, 1, 2, 3, 4, 5, 6]);
+        assert_eq!(*history.state_at(7), 3);
+        assert_eq!(*history.state_at(1_000_000_000), 4);
+
+        assert_eq!(find_cycle_hashed(0, rho, 5), None);
+    }
+
+    #[test]
+    fn test_cycle_project() {
+        assert_eq!(RHO_CYCLE.project(2), 2);
+        assert_eq!(RHO_CYCLE.project(3), 3);
+        assert_eq!(RHO_CYCLE.project(7), 3);
+        assert_eq!(RHO_CYCLE.project(12), 4);
+    }
+
+    #[test]
+    fn test_state_at_step() {
+        assert_eq!(state_at_step(0, rho, 0), 0);
+        assert_eq!(state_at_step(0, rho, 5), 5);
+        assert_eq!(state_at_step(0, rho, 1_000_000_000), 4);
+    }
+}
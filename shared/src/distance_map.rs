@@ -0,0 +1,135 @@
+use std::collections::VecDeque;
+
+use array2d::Array2D;
+
+use crate::{Adjacents, ArrayPosition, adjacent_positions_iter};
+
+/// Breadth first search out from all of the sources at once
+/// Each reachable cell holds the number of steps to its nearest source, walls and unreachable cells are None
+pub fn bfs_distance_map<T, F>(
+    array: &Array2D<T>,
+    sources: &[ArrayPosition],
+    connectivity: Adjacents,
+    is_wall: F,
+) -> Array2D<Option<u32>>
+where
+    F: Fn(&T) -> bool,
+{
+    let mut distances = Array2D::filled_with(None, array.num_rows(), array.num_columns());
+    let mut queue = VecDeque::with_capacity(sources.len());
+    for source in sources {
+        if distances[*source].is_none() {
+            distances[*source] = Some(0);
+            queue.push_back(*source);
+        }
+    }
+
+    while let Some(pos) = queue.pop_front() {
+        let next_distance = distances[pos].unwrap() + 1;
+        for next in adjacent_positions_iter(array, pos, connectivity) {
+            if distances[next].is_none() && !is_wall(&array[next]) {
+                distances[next] = Some(next_distance);
+                queue.push_back(next);
+            }
+        }
+    }
+    distances
+}
+
+/// Walk back down the distance map from target to the nearest source
+/// Returns the path from the source to the target, or None if target was not reached
+pub fn path_from_distance_map(
+    distances: &Array2D<Option<u32>>,
+    target: ArrayPosition,
+    connectivity: Adjacents,
+) -> Option<Vec<ArrayPosition>> {
+    let mut distance = (*distances.get(target.0, target.1)?)?;
+    let mut path = vec![target];
+    let mut pos = target;
+    while distance > 0 {
+        pos = adjacent_positions_iter(distances, pos, connectivity)
+            .find(|next| distances[*next] == Some(distance - 1))?;
+        path.push(pos);
+        distance -= 1;
+    }
+    path.reverse();
+    Some(path)
+}
+
+/// All cells that are at most max_distance steps from a source
+pub fn cells_within_distance(
+    distances: &Array2D<Option<u32>>,
+    max_distance: u32,
+) -> Vec<ArrayPosition> {
+    distances
+        .enumerate_row_major()
+        .filter_map(|(pos, distance)| match distance {
+            Some(distance) if *distance <= max_distance => Some(pos),
+            _ => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::array2d::grid_from_lines;
+
+    #[test]
+    fn test_bfs_distance_map_single_source() {
+        let grid = grid_from_lines(&["....", ".##.", "...#", "#..#"]);
+        let distances = bfs_distance_map(&grid, &[(0, 0)], Adjacents::CROSS, |v| *v == b'#');
+        let expected = Array2D::from_rows(&[
+            vec![Some(0), Some(1), Some(2), Some(3)],
+            vec![Some(1), None, None, Some(4)],
+            vec![Some(2), Some(3), Some(4), None],
+            vec![None, Some(4), Some(5), None],
+        ])
+        .unwrap();
+        assert_eq!(distances, expected);
+    }
+
+    #[test]
+    fn test_bfs_distance_map_multi_source() {
+        let grid = Array2D::filled_with(b'.', 1, 7);
+        let distances = bfs_distance_map(&grid, &[(0, 0), (0, 6)], Adjacents::CROSS, |_| false);
+        let row: Vec<Option<u32>> = distances.elements_row_major_iter().copied().collect();
+        assert_eq!(row, [0, 1, 2, 3, 2, 1, 0].map(Some));
+        assert_eq!(cells_within_distance(&distances, 1).len(), 4);
+    }
+
+    #[test]
+    fn test_bfs_distance_map_diagonal() {
+        let grid = Array2D::filled_with(0u8, 5, 5);
+        let distances = bfs_distance_map(&grid, &[(0, 0)], Adjacents::ALL, |_| false);
+        assert_eq!(distances[(4, 4)], Some(4));
+        assert_eq!(distances[(4, 2)], Some(4));
+        assert_eq!(distances[(1, 3)], Some(3));
+    }
+
+    #[test]
+    fn test_path_from_distance_map() {
+        let grid = grid_from_lines(&["....", ".##.", "...#", "#..#"]);
+        let distances = bfs_distance_map(&grid, &[(0, 0)], Adjacents::CROSS, |v| *v == b'#');
+        let path = path_from_distance_map(&distances, (3, 2), Adjacents::CROSS).unwrap();
+        assert_eq!(path.len(), 6);
+        assert_eq!(path[0], (0, 0));
+        assert_eq!(path[5], (3, 2));
+        for step in path.windows(2) {
+            assert_eq!(
+                step[0].0.abs_diff(step[1].0) + step[0].1.abs_diff(step[1].1),
+                1
+            );
+            assert_ne!(grid[step[1]], b'#');
+        }
+
+        assert_eq!(
+            path_from_distance_map(&distances, (1, 1), Adjacents::CROSS),
+            None
+        );
+        assert_eq!(
+            path_from_distance_map(&distances, (0, 0), Adjacents::CROSS),
+            Some(vec![(0, 0)])
+        );
+    }
+}
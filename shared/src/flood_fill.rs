@@ -1,26 +1,92 @@
+use std::collections::HashSet;
+
 use array2d::Array2D;
 
-use crate::{Adjacents, ArrayPosition, adjacent_positions};
+use crate::{Adjacents, ArrayPosition, GridBounds, adjacent_positions_iter};
 
-pub fn flood_fill(array_in: &mut Array2D<u8>, start: ArrayPosition, empty_char: u8, fill_with: u8) {
-    // Starting at start, fill outwards all empty_char spots
-    // creeps outwards in place to bound ram
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FloodFillResult {
+    pub cells: HashSet<ArrayPosition>,
+    /// None when nothing was filled
+    pub bounds: Option<GridBounds>,
+}
 
-    recursively_fill(array_in, start, empty_char, fill_with);
+impl FloodFillResult {
+    pub fn count(&self) -> usize {
+        self.cells.len()
+    }
 }
-fn recursively_fill(
-    array: &mut Array2D<u8>,
-    start_position: ArrayPosition,
+
+pub fn flood_fill(
+    array_in: &mut Array2D<u8>,
+    start: ArrayPosition,
     empty_char: u8,
     fill_with: u8,
-) {
-    for spot in adjacent_positions(array, start_position, Adjacents::CROSS) {
-        //All adjacent spots
-        if array.get(spot.0, spot.1).unwrap() == &empty_char {
-            array.set(spot.0, spot.1, fill_with).unwrap();
-            recursively_fill(array, spot, empty_char, fill_with);
+) -> FloodFillResult {
+    // Starting at start, fill outwards all empty_char spots
+    flood_fill_with(
+        array_in,
+        start,
+        Adjacents::CROSS,
+        |value| *value == empty_char,
+        fill_with,
+    )
+}
+
+/// Fill every cell connected to start (including start) that matches the predicate
+pub fn flood_fill_with<T, F>(
+    array: &mut Array2D<T>,
+    start: ArrayPosition,
+    connectivity: Adjacents,
+    predicate: F,
+    fill_with: T,
+) -> FloodFillResult
+where
+    T: Clone,
+    F: Fn(&T) -> bool,
+{
+    let region = flood_region(array, start, connectivity, predicate);
+    for pos in &region.cells {
+        array[*pos] = fill_with.clone();
+    }
+    region
+}
+
+/// Find every cell connected to start (including start) that matches the predicate, without modifying the array
+pub fn flood_region<T, F>(
+    array: &Array2D<T>,
+    start: ArrayPosition,
+    connectivity: Adjacents,
+    predicate: F,
+) -> FloodFillResult
+where
+    F: Fn(&T) -> bool,
+{
+    let mut cells = HashSet::new();
+    let mut bounds: Option<GridBounds> = None;
+    if array
+        .get(start.0, start.1)
+        .is_none_or(|value| !predicate(value))
+    {
+        return FloodFillResult { cells, bounds };
+    }
+
+    // Explicit stack rather than recursion so large areas can't overflow
+    let mut to_visit = vec![start];
+    cells.insert(start);
+    while let Some(pos) = to_visit.pop() {
+        match bounds.as_mut() {
+            Some(bounds) => bounds.include(pos),
+            None => bounds = Some(GridBounds::new(pos)),
+        }
+        for spot in adjacent_positions_iter(array, pos, connectivity) {
+            if !cells.contains(&spot) && predicate(&array[spot]) {
+                cells.insert(spot);
+                to_visit.push(spot);
+            }
         }
     }
+    FloodFillResult { cells, bounds }
 }
 #[cfg(test)]
 mod tests {
@@ -46,7 +112,7 @@ mod tests {
         )
         .unwrap();
 
-        flood_fill(&mut grid, (9, 9), b'.', b'x');
+        let result = flood_fill(&mut grid, (9, 9), b'.', b'x');
         let expected = Array2D::from_row_major(
             &vec![
                 b'x', b'x', b'x', b'x', b'x', b'x', b'x', b'O', b'.', b'.', //
@@ -65,5 +131,51 @@ mod tests {
         )
         .unwrap();
         assert_eq!(grid, expected);
+        assert_eq!(result.count(), 47);
+        let bounds = result.bounds.unwrap();
+        assert_eq!((bounds.min, bounds.max), ((0, 0), (9, 9)));
+    }
+
+    #[test]
+    fn test_flood_fill_fills_start_cell() {
+        let mut grid = Array2D::filled_with(b'.', 1, 1);
+        let result = flood_fill(&mut grid, (0, 0), b'.', b'x');
+        assert_eq!(grid[(0, 0)], b'x');
+        assert_eq!(result.count(), 1);
+    }
+
+    #[test]
+    fn test_flood_fill_start_not_matching() {
+        let mut grid = Array2D::filled_with(b'#', 3, 3);
+        let result = flood_fill(&mut grid, (1, 1), b'.', b'x');
+        assert_eq!(result.count(), 0);
+        assert_eq!(result.bounds, None);
+        assert_eq!(grid, Array2D::filled_with(b'#', 3, 3));
+    }
+
+    #[test]
+    fn test_flood_fill_with_connectivity() {
+        // Diagonal line of cells only joins up when diagonals count
+        let mut grid = Array2D::filled_with(0u32, 4, 4);
+        for i in 0..4 {
+            grid[(i, i)] = 1;
+        }
+        let cross = flood_region(&grid, (0, 0), Adjacents::CROSS, |v| *v == 1);
+        assert_eq!(cross.count(), 1);
+
+        let result = flood_fill_with(&mut grid, (0, 0), Adjacents::ALL, |v| *v == 1, 7);
+        assert_eq!(result.count(), 4);
+        let bounds = result.bounds.unwrap();
+        assert_eq!((bounds.min, bounds.max), ((0, 0), (3, 3)));
+        assert!((0..4).all(|i| grid[(i, i)] == 7));
+    }
+
+    #[test]
+    fn test_flood_fill_large_area() {
+        // Big enough that the old recursive fill blew the stack
+        let mut grid = Array2D::filled_with(b'.', 400, 400);
+        let result = flood_fill(&mut grid, (0, 0), b'.', b'x');
+        assert_eq!(result.count(), 160_000);
+        assert!(grid.elements_row_major_iter().all(|v| *v == b'x'));
     }
 }
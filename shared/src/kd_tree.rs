@@ -0,0 +1,371 @@
+use std::collections::VecDeque;
+
+use num_traits::Num;
+
+use crate::space_2d::abs_diff;
+use crate::{XYPos, XYZPos};
+
+/// A point that can be stored in a `KdTree`
+pub trait KdPoint: Copy {
+    type Scalar: Num + Copy + PartialOrd;
+    const DIMENSIONS: usize;
+    fn coordinate(&self, axis: usize) -> Self::Scalar;
+    fn squared_distance(&self, other: &Self) -> Self::Scalar;
+}
+
+impl<T: Num + Copy + PartialOrd> KdPoint for XYPos<T> {
+    type Scalar = T;
+    const DIMENSIONS: usize = 2;
+    fn coordinate(&self, axis: usize) -> T {
+        match axis {
+            0 => self.x,
+            _ => self.y,
+        }
+    }
+    fn squared_distance(&self, other: &Self) -> T {
+        self.squared_distance_to(other)
+    }
+}
+
+impl<T: Num + Copy + PartialOrd> KdPoint for XYZPos<T> {
+    type Scalar = T;
+    const DIMENSIONS: usize = 3;
+    fn coordinate(&self, axis: usize) -> T {
+        match axis {
+            0 => self.x,
+            1 => self.y,
+            _ => self.z,
+        }
+    }
+    fn squared_distance(&self, other: &Self) -> T {
+        self.squared_distance_to(other)
+    }
+}
+
+/// Spatial index over a fixed set of points
+/// Results refer to points by their index in the slice the tree was built from,
+/// and all distances are squared so integer points stay exact
+pub struct KdTree<P: KdPoint> {
+    points: Vec<P>,
+    // Balanced tree stored implicitly, each node is the median of its slice with the halves either side
+    order: Vec<usize>,
+}
+
+impl<P: KdPoint> KdTree<P> {
+    pub fn new(points: &[P]) -> Self {
+        let mut order: Vec<usize> = (0..points.len()).collect();
+        build(points, &mut order, 0);
+        KdTree {
+            points: points.to_vec(),
+            order,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+    pub fn points(&self) -> &[P] {
+        &self.points
+    }
+
+    /// The k points closest to target as (index, squared distance), closest first
+    pub fn nearest(&self, target: &P, k: usize) -> Vec<(usize, P::Scalar)> {
+        let mut best = Vec::with_capacity(k + 1);
+        if k > 0 {
+            self.nearest_in(target, k, &self.order, 0, &mut best);
+        }
+        best
+    }
+
+    fn nearest_in(
+        &self,
+        target: &P,
+        k: usize,
+        nodes: &[usize],
+        depth: usize,
+        best: &mut Vec<(usize, P::Scalar)>,
+    ) {
+        if nodes.is_empty() {
+            return;
+        }
+        let mid = nodes.len() / 2;
+        let index = nodes[mid];
+        let point = &self.points[index];
+
+        // Keep best sorted by distance, dropping anything past k
+        let distance = point.squared_distance(target);
+        if best.len() < k || distance < best[best.len() - 1].1 {
+            let at = best.partition_point(|(_, d)| *d <= distance);
+            best.insert(at, (index, distance));
+            best.truncate(k);
+        }
+
+        let axis = depth % P::DIMENSIONS;
+        let offset = abs_diff(target.coordinate(axis), point.coordinate(axis));
+        let (near, far) = if target.coordinate(axis) < point.coordinate(axis) {
+            (&nodes[..mid], &nodes[mid + 1..])
+        } else {
+            (&nodes[mid + 1..], &nodes[..mid])
+        };
+        self.nearest_in(target, k, near, depth + 1, best);
+        // Only look past the splitting plane if something over there could be closer
+        if best.len() < k || offset * offset <= best[best.len() - 1].1 {
+            self.nearest_in(target, k, far, depth + 1, best);
+        }
+    }
+
+    /// Every point within the radius of target (inclusive) as (index, squared distance), closest first
+    pub fn within_radius(&self, target: &P, squared_radius: P::Scalar) -> Vec<(usize, P::Scalar)> {
+        let mut found = Vec::new();
+        self.within_radius_in(target, squared_radius, &self.order, 0, &mut found);
+        found.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap().then(a.0.cmp(&b.0)));
+        found
+    }
+
+    fn within_radius_in(
+        &self,
+        target: &P,
+        squared_radius: P::Scalar,
+        nodes: &[usize],
+        depth: usize,
+        found: &mut Vec<(usize, P::Scalar)>,
+    ) {
+        if nodes.is_empty() {
+            return;
+        }
+        let mid = nodes.len() / 2;
+        let index = nodes[mid];
+        let point = &self.points[index];
+        let distance = point.squared_distance(target);
+        if distance <= squared_radius {
+            found.push((index, distance));
+        }
+        let axis = depth % P::DIMENSIONS;
+        let offset = abs_diff(target.coordinate(axis), point.coordinate(axis));
+        let target_below = target.coordinate(axis) < point.coordinate(axis);
+        if target_below || offset * offset <= squared_radius {
+            self.within_radius_in(target, squared_radius, &nodes[..mid], depth + 1, found);
+        }
+        if !target_below || offset * offset <= squared_radius {
+            self.within_radius_in(target, squared_radius, &nodes[mid + 1..], depth + 1, found);
+        }
+    }
+
+    /// Every pair of points (i, j, squared distance) with i < j, in order of increasing distance
+    /// Pairs are found lazily, so taking the first few is much cheaper than building all n^2 of them
+    pub fn nearest_pairs(&self) -> NearestPairs<'_, P> {
+        NearestPairs {
+            tree: self,
+            neighbours: 1,
+            emitted_below: None,
+            batch: VecDeque::new(),
+            finished: self.points.len() < 2,
+        }
+    }
+}
+
+fn build<P: KdPoint>(points: &[P], nodes: &mut [usize], depth: usize) {
+    if nodes.len() <= 1 {
+        return;
+    }
+    let axis = depth % P::DIMENSIONS;
+    let mid = nodes.len() / 2;
+    nodes.select_nth_unstable_by(mid, |a, b| {
+        points[*a]
+            .coordinate(axis)
+            .partial_cmp(&points[*b].coordinate(axis))
+            .unwrap()
+    });
+    let (below, above) = nodes.split_at_mut(mid);
+    build(points, below, depth + 1);
+    build(points, &mut above[1..], depth + 1);
+}
+
+/// Iterator from `KdTree::nearest_pairs`
+pub struct NearestPairs<'a, P: KdPoint> {
+    tree: &'a KdTree<P>,
+    // How many neighbours of each point the next batch looks at, doubled every batch
+    neighbours: usize,
+    // Every pair closer than this has already been returned
+    emitted_below: Option<P::Scalar>,
+    batch: VecDeque<(usize, usize, P::Scalar)>,
+    finished: bool,
+}
+
+impl<P: KdPoint> NearestPairs<'_, P> {
+    fn next_batch(&mut self) {
+        let tree = self.tree;
+        let everything = self.neighbours + 1 >= tree.len();
+        let mut candidates = Vec::new();
+        // A pair can only be missed if it is further away than some point's furthest neighbour
+        let mut safe_below: Option<P::Scalar> = None;
+        for (i, point) in tree.points.iter().enumerate() {
+            let nearest = tree.nearest(point, self.neighbours + 1);
+            if let Some((_, furthest)) = nearest.last()
+                && !everything
+                && safe_below.is_none_or(|safe| *furthest < safe)
+            {
+                safe_below = Some(*furthest);
+            }
+            candidates.extend(
+                nearest
+                    .into_iter()
+                    .filter(|(j, _)| *j > i)
+                    .map(|(j, distance)| (i, j, distance)),
+            );
+        }
+        candidates.retain(|(_, _, distance)| {
+            self.emitted_below.is_none_or(|below| *distance >= below)
+                && safe_below.is_none_or(|safe| *distance < safe)
+        });
+        candidates.sort_by(|a, b| {
+            a.2.partial_cmp(&b.2)
+                .unwrap()
+                .then((a.0, a.1).cmp(&(b.0, b.1)))
+        });
+        self.batch.extend(candidates);
+
+        self.finished = everything;
+        self.emitted_below = safe_below;
+        self.neighbours *= 2;
+    }
+}
+
+impl<P: KdPoint> Iterator for NearestPairs<'_, P> {
+    type Item = (usize, usize, P::Scalar);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.batch.is_empty() && !self.finished {
+            self.next_batch();
+        }
+        self.batch.pop_front()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_points() -> Vec<XYZPos<i64>> {
+        [
+            "162,817,812",
+            "57,618,57",
+            "906,360,560",
+            "592,479,940",
+            "352,342,300",
+            "466,668,158",
+            "542,29,236",
+            "431,825,988",
+            "739,650,466",
+            "52,470,668",
+            "216,146,977",
+            "819,987,18",
+            "117,168,530",
+            "805,96,715",
+            "346,949,466",
+            "970,615,88",
+            "941,993,340",
+            "862,61,35",
+            "984,92,344",
+            "425,690,689",
+        ]
+        .iter()
+        .map(|line| line.parse().unwrap())
+        .collect()
+    }
+
+    fn brute_force_pairs(points: &[XYZPos<i64>]) -> Vec<(usize, usize, i64)> {
+        let mut pairs = Vec::new();
+        for i in 0..points.len() {
+            for j in i + 1..points.len() {
+                pairs.push((i, j, points[i].squared_distance_to(&points[j])));
+            }
+        }
+        pairs.sort_by_key(|(i, j, distance)| (*distance, *i, *j));
+        pairs
+    }
+
+    #[test]
+    fn test_kd_tree_nearest() {
+        let points = sample_points();
+        let tree = KdTree::new(&points);
+        for target in &points {
+            let mut expected: Vec<(usize, i64)> = points
+                .iter()
+                .enumerate()
+                .map(|(i, p)| (i, p.squared_distance_to(target)))
+                .collect();
+            expected.sort_by_key(|(i, distance)| (*distance, *i));
+            let found = tree.nearest(target, 4);
+            let distances: Vec<i64> = found.iter().map(|(_, d)| *d).collect();
+            let expected_distances: Vec<i64> = expected.iter().take(4).map(|(_, d)| *d).collect();
+            assert_eq!(distances, expected_distances);
+            // Closest is always the point itself
+            assert_eq!(found[0].1, 0);
+        }
+        assert!(tree.nearest(&points[0], 0).is_empty());
+        assert_eq!(tree.nearest(&points[0], 100).len(), 20);
+    }
+
+    #[test]
+    fn test_kd_tree_within_radius() {
+        let points: Vec<XYPos<i64>> = (0..10)
+            .flat_map(|x| (0..10).map(move |y| XYPos::new(x, y)))
+            .collect();
+        let tree = KdTree::new(&points);
+        let found = tree.within_radius(&XYPos::new(5, 5), 1);
+        assert_eq!(found.len(), 5);
+        assert_eq!(found[0], (55, 0));
+        assert_eq!(tree.within_radius(&XYPos::new(0, 0), 4).len(), 6);
+        assert!(tree.within_radius(&XYPos::new(-5, -5), 4).is_empty());
+    }
+
+    #[test]
+    fn test_kd_tree_unsigned_points() {
+        let points: Vec<XYPos<u32>> = (0..10)
+            .flat_map(|x| (0..10).map(move |y| XYPos::new(x, y)))
+            .collect();
+        let tree = KdTree::new(&points);
+        let distances: Vec<u32> = tree
+            .nearest(&XYPos::new(0, 0), 4)
+            .iter()
+            .map(|(_, d)| *d)
+            .collect();
+        assert_eq!(distances, [0, 1, 1, 2]);
+        assert_eq!(tree.nearest(&XYPos::new(20, 20), 1), [(99, 242)]);
+        assert_eq!(tree.within_radius(&XYPos::new(5, 5), 1).len(), 5);
+        assert_eq!(tree.nearest_pairs().next(), Some((0, 1, 1)));
+    }
+
+    #[test]
+    fn test_kd_tree_nearest_pairs() {
+        let points = sample_points();
+        let tree = KdTree::new(&points);
+        let pairs: Vec<(usize, usize, i64)> = tree.nearest_pairs().collect();
+        assert_eq!(pairs, brute_force_pairs(&points));
+        // The closest pair in the day 8 sample
+        assert_eq!((pairs[0].0, pairs[0].1), (0, 19));
+    }
+
+    #[test]
+    fn test_kd_tree_nearest_pairs_with_ties() {
+        // Grid points have lots of equal distances
+        let points: Vec<XYPos<i64>> = (0..6)
+            .flat_map(|x| (0..5).map(move |y| XYPos::new(x * 2, y * 3)))
+            .collect();
+        let tree = KdTree::new(&points);
+        let pairs: Vec<(usize, usize, i64)> = tree.nearest_pairs().collect();
+        let mut expected = Vec::new();
+        for i in 0..points.len() {
+            for j in i + 1..points.len() {
+                expected.push((i, j, points[i].squared_distance_to(&points[j])));
+            }
+        }
+        expected.sort_by_key(|(i, j, distance)| (*distance, *i, *j));
+        assert_eq!(pairs, expected);
+        assert_eq!(KdTree::new(&points[..1]).nearest_pairs().count(), 0);
+    }
+}
@@ -0,0 +1,102 @@
+use array2d::Array2D;
+
+use crate::Adjacents;
+
+/// Set of (row, col) offsets around a cell to look at, i.e. the 8 neighbours or a knight's moves
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Stencil {
+    offsets: Vec<(i64, i64)>,
+}
+
+impl Stencil {
+    pub fn from_offsets(offsets: &[(i64, i64)]) -> Self {
+        Stencil {
+            offsets: offsets.to_vec(),
+        }
+    }
+    pub fn offsets(&self) -> &[(i64, i64)] {
+        &self.offsets
+    }
+}
+
+impl From<Adjacents> for Stencil {
+    fn from(adjacents: Adjacents) -> Self {
+        Stencil::from_offsets(adjacents.get_pattern())
+    }
+}
+
+/// For every cell, count how many cells under the stencil match the predicate
+/// Offsets that land outside the array are skipped
+pub fn count_neighbours<T, F>(array: &Array2D<T>, stencil: &Stencil, predicate: F) -> Array2D<u32>
+where
+    F: Fn(&T) -> bool,
+{
+    sum_neighbours(array, stencil, |value| predicate(value) as u32)
+}
+
+/// For every cell, add up the value of each cell under the stencil
+/// Offsets that land outside the array are skipped
+pub fn sum_neighbours<T, F>(array: &Array2D<T>, stencil: &Stencil, value: F) -> Array2D<u32>
+where
+    F: Fn(&T) -> u32,
+{
+    let (num_rows, num_columns) = (array.num_rows() as i64, array.num_columns() as i64);
+    // Work out each cell's value once, then add it to every cell that sees it through the stencil
+    let values: Vec<u32> = array.elements_row_major_iter().map(value).collect();
+    let mut sums = vec![0; values.len()];
+    for (dr, dc) in stencil.offsets() {
+        // Only the cells whose (row + dr, col + dc) is in bounds
+        let rows = (-dr).max(0)..(num_rows - dr).min(num_rows);
+        let columns = (-dc).max(0)..(num_columns - dc).min(num_columns);
+        for row in rows {
+            let target = row * num_columns;
+            let source = (row + dr) * num_columns + dc;
+            for col in columns.clone() {
+                sums[(target + col) as usize] += values[(source + col) as usize];
+            }
+        }
+    }
+    Array2D::from_row_major(&sums, array.num_rows(), array.num_columns()).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::array2d::grid_from_lines;
+
+    #[test]
+    fn test_count_neighbours_adjacents() {
+        let grid = grid_from_lines(&["@@.", "@.@", "..@"]);
+        let counts = count_neighbours(&grid, &Adjacents::ALL.into(), |v| *v == b'@');
+        assert_eq!(
+            counts.as_rows(),
+            vec![vec![2, 3, 2], vec![2, 5, 2], vec![1, 3, 1]]
+        );
+        let counts = count_neighbours(&grid, &Adjacents::CROSS.into(), |v| *v == b'@');
+        assert_eq!(
+            counts.as_rows(),
+            vec![vec![2, 1, 2], vec![1, 3, 1], vec![1, 1, 1]]
+        );
+    }
+
+    #[test]
+    fn test_count_neighbours_matches_adjacent_positions() {
+        let grid = grid_from_lines(&["..@@.@@@@.", "@@@.@.@.@@", "@@@@@.@.@@", "@.@@@@..@."]);
+        let counts = count_neighbours(&grid, &Adjacents::ALL.into(), |v| *v == b'@');
+        for (pos, count) in counts.enumerate_row_major() {
+            let expected = crate::adjacent_positions_iter(&grid, pos, Adjacents::ALL)
+                .filter(|p| grid[*p] == b'@')
+                .count();
+            assert_eq!(*count as usize, expected);
+        }
+    }
+
+    #[test]
+    fn test_sum_neighbours_custom_stencil() {
+        let grid = Array2D::from_rows(&[vec![1, 2, 3], vec![4, 5, 6]]).unwrap();
+        // Cell itself plus the cell two to the right
+        let stencil = Stencil::from_offsets(&[(0, 0), (0, 2)]);
+        let sums = sum_neighbours(&grid, &stencil, |v| *v);
+        assert_eq!(sums.as_rows(), vec![vec![4, 2, 3], vec![10, 5, 6]]);
+    }
+}
@@ -0,0 +1,120 @@
+use array2d::Array2D;
+
+use crate::{ArrayPosition, CoordinateCompression, fill_polygon};
+
+/// Prefix sums over a grid, so the total of any rectangle can be read in constant time
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SummedAreaTable {
+    // One extra row and column of zeros at the top and left to avoid edge cases
+    sums: Array2D<i64>,
+}
+
+impl SummedAreaTable {
+    pub fn new<T, F>(array: &Array2D<T>, value: F) -> Self
+    where
+        F: Fn(&T) -> i64,
+    {
+        let mut sums = Array2D::filled_with(0, array.num_rows() + 1, array.num_columns() + 1);
+        for ((row, col), cell) in array.enumerate_row_major() {
+            sums[(row + 1, col + 1)] =
+                value(cell) + sums[(row, col + 1)] + sums[(row + 1, col)] - sums[(row, col)];
+        }
+        SummedAreaTable { sums }
+    }
+
+    /// Total of the rectangle with corners a and b (inclusive), in any order
+    pub fn sum(&self, a: ArrayPosition, b: ArrayPosition) -> i64 {
+        let (top, bottom) = (a.0.min(b.0), a.0.max(b.0) + 1);
+        let (left, right) = (a.1.min(b.1), a.1.max(b.1) + 1);
+        self.sums[(bottom, right)] - self.sums[(top, right)] - self.sums[(bottom, left)]
+            + self.sums[(top, left)]
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VertexRectangle {
+    /// Opposite corners of the rectangle, both vertices of the polygon
+    pub corners: ((i64, i64), (i64, i64)),
+    /// Number of lattice points covered, including the edges
+    pub area: i64,
+}
+
+/// The largest axis aligned rectangle with two opposite corners on vertices of the rectilinear polygon
+/// that lies entirely inside (or on the edge of) the polygon
+/// Vertices are in order around the polygon, None if there are fewer than 2
+pub fn largest_rectangle_in_polygon(vertices: &[(i64, i64)]) -> Option<VertexRectangle> {
+    // Only a few hundred distinct rows/cols, so work on the compressed grid
+    let compression = CoordinateCompression::from_points(vertices);
+    let compressed = compression.compress_points(vertices);
+    let mut grid = compression.filled_grid(false);
+    fill_polygon(&mut grid, &compressed, true);
+    let outside = SummedAreaTable::new(&grid, |inside| !*inside as i64);
+
+    let mut best: Option<VertexRectangle> = None;
+    for (i, (a, compressed_a)) in vertices.iter().zip(&compressed).enumerate() {
+        for (b, compressed_b) in vertices.iter().zip(&compressed).skip(i + 1) {
+            let area = ((a.0 - b.0).abs() + 1) * ((a.1 - b.1).abs() + 1);
+            if best.is_some_and(|best| best.area >= area) {
+                continue;
+            }
+            if outside.sum(*compressed_a, *compressed_b) == 0 {
+                best = Some(VertexRectangle {
+                    corners: (*a, *b),
+                    area,
+                });
+            }
+        }
+    }
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summed_area_table() {
+        let grid = Array2D::from_rows(&[vec![1, 2, 3], vec![4, 5, 6], vec![7, 8, 9]]).unwrap();
+        let table = SummedAreaTable::new(&grid, |v| *v);
+        assert_eq!(table.sum((0, 0), (2, 2)), 45);
+        assert_eq!(table.sum((1, 1), (1, 1)), 5);
+        assert_eq!(table.sum((2, 1), (1, 2)), 28);
+        assert_eq!(table.sum((0, 2), (2, 2)), 18);
+    }
+
+    #[test]
+    fn test_largest_rectangle_in_polygon() {
+        // Day 9 sample in (x, y)
+        let vertices = [
+            (7, 1),
+            (11, 1),
+            (11, 7),
+            (9, 7),
+            (9, 5),
+            (2, 5),
+            (2, 3),
+            (7, 3),
+        ];
+        let best = largest_rectangle_in_polygon(&vertices).unwrap();
+        assert_eq!(best.area, 24);
+        assert_eq!(best.corners, ((9, 5), (2, 3)));
+    }
+
+    #[test]
+    fn test_largest_rectangle_skips_notch() {
+        // U shape, the corners across the notch would make a bigger rectangle that isn't inside
+        let vertices = [
+            (0, 0),
+            (10, 0),
+            (10, 10),
+            (7, 10),
+            (7, 2),
+            (3, 2),
+            (3, 10),
+            (0, 10),
+        ];
+        let best = largest_rectangle_in_polygon(&vertices).unwrap();
+        assert_eq!(best.area, 4 * 11);
+        assert_eq!(largest_rectangle_in_polygon(&[(0, 0)]), None);
+    }
+}
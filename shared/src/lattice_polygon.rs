@@ -0,0 +1,231 @@
+use crate::rasterise::polygon_edges;
+use crate::raycast::gcd;
+
+/// Exact polygon maths on integer vertices, without rasterising anything
+/// Vertices are (x, y) in order around the polygon, the last one joins back to the first
+/// (row, col) works just as well as long as every point uses the same order
+pub type LatticePoint = (i64, i64);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PointLocation {
+    Inside,
+    Outside,
+    OnBoundary,
+}
+
+/// Twice the signed area using the shoelace formula, doubled so it is always a whole number
+/// Positive when the vertices go anticlockwise with y pointing up
+pub fn shoelace_doubled_area(vertices: &[LatticePoint]) -> i64 {
+    polygon_edges(vertices)
+        .map(|(a, b)| a.0 * b.1 - b.0 * a.1)
+        .sum()
+}
+
+/// Number of lattice points on the outline, including the vertices
+pub fn lattice_boundary_points(vertices: &[LatticePoint]) -> i64 {
+    polygon_edges(vertices)
+        .map(|(a, b)| gcd((b.0 - a.0).unsigned_abs(), (b.1 - a.1).unsigned_abs()) as i64)
+        .sum()
+}
+
+/// Number of lattice points strictly inside the polygon, using Pick's theorem A = I + B/2 - 1
+/// The vertices must form a simple polygon, a flat outline (fewer than 3 vertices, or all on one line)
+/// has nothing inside
+pub fn lattice_interior_points(vertices: &[LatticePoint]) -> i64 {
+    let doubled_area = shoelace_doubled_area(vertices).abs();
+    // Pick's theorem goes negative without any area, as the outline is walked there and back
+    if doubled_area == 0 {
+        return 0;
+    }
+    (doubled_area - lattice_boundary_points(vertices) + 2) / 2
+}
+
+/// Number of lattice points inside or on the outline
+/// When each vertex is the centre of a grid cell this is the number of cells the filled shape covers
+/// A flat outline covers the points on the line between its two furthest apart vertices
+pub fn lattice_points_covered(vertices: &[LatticePoint]) -> i64 {
+    if shoelace_doubled_area(vertices) == 0 {
+        // The outline doubles back on itself, so only count each point on the line once
+        let (Some(first), Some(last)) = (vertices.iter().min(), vertices.iter().max()) else {
+            return 0;
+        };
+        return gcd(
+            (last.0 - first.0).unsigned_abs(),
+            (last.1 - first.1).unsigned_abs(),
+        ) as i64
+            + 1;
+    }
+    lattice_interior_points(vertices) + lattice_boundary_points(vertices)
+}
+
+/// Where the point sits relative to any simple polygon
+pub fn point_in_polygon(vertices: &[LatticePoint], point: LatticePoint) -> PointLocation {
+    let mut inside = false;
+    for (a, b) in polygon_edges(vertices) {
+        if on_segment(a, b, point) {
+            return PointLocation::OnBoundary;
+        }
+        // Count edges crossing a ray to +x, each edge covers min_y <= y < max_y so vertices count once
+        if (a.1 > point.1) != (b.1 > point.1) {
+            // Is the crossing to the right of the point, kept in integers to stay exact
+            let crossing = (b.0 - a.0) as i128 * (point.1 - a.1) as i128;
+            let offset = (point.0 - a.0) as i128 * (b.1 - a.1) as i128;
+            let right_of_point = if b.1 > a.1 {
+                crossing > offset
+            } else {
+                crossing < offset
+            };
+            if right_of_point {
+                inside = !inside;
+            }
+        }
+    }
+    if inside {
+        PointLocation::Inside
+    } else {
+        PointLocation::Outside
+    }
+}
+
+/// Faster `point_in_polygon` for polygons whose edges are all horizontal or vertical
+pub fn point_in_rectilinear_polygon(
+    vertices: &[LatticePoint],
+    point: LatticePoint,
+) -> PointLocation {
+    let mut inside = false;
+    for (a, b) in polygon_edges(vertices) {
+        debug_assert!(
+            a.0 == b.0 || a.1 == b.1,
+            "Edge {a:?} -> {b:?} is not rectilinear"
+        );
+        let (min_x, max_x) = (a.0.min(b.0), a.0.max(b.0));
+        let (min_y, max_y) = (a.1.min(b.1), a.1.max(b.1));
+        if (min_x..=max_x).contains(&point.0) && (min_y..=max_y).contains(&point.1) {
+            return PointLocation::OnBoundary;
+        }
+        // Only vertical edges can cross a ray to +x
+        if a.0 == b.0 && a.0 > point.0 && min_y <= point.1 && point.1 < max_y {
+            inside = !inside;
+        }
+    }
+    if inside {
+        PointLocation::Inside
+    } else {
+        PointLocation::Outside
+    }
+}
+
+fn on_segment(a: LatticePoint, b: LatticePoint, point: LatticePoint) -> bool {
+    let cross = (b.0 - a.0) as i128 * (point.1 - a.1) as i128
+        - (b.1 - a.1) as i128 * (point.0 - a.0) as i128;
+    cross == 0
+        && a.0.min(b.0) <= point.0
+        && point.0 <= a.0.max(b.0)
+        && a.1.min(b.1) <= point.1
+        && point.1 <= a.1.max(b.1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Day 9 sample
+    const SAMPLE: [LatticePoint; 8] = [
+        (7, 1),
+        (11, 1),
+        (11, 7),
+        (9, 7),
+        (9, 5),
+        (2, 5),
+        (2, 3),
+        (7, 3),
+    ];
+
+    #[test]
+    fn test_shoelace_area() {
+        let square = [(0, 0), (4, 0), (4, 4), (0, 4)];
+        assert_eq!(shoelace_doubled_area(&square), 32);
+        let clockwise: Vec<LatticePoint> = square.iter().rev().copied().collect();
+        assert_eq!(shoelace_doubled_area(&clockwise), -32);
+        assert_eq!(shoelace_doubled_area(&[(0, 0), (3, 0), (0, 1)]), 3);
+    }
+
+    #[test]
+    fn test_picks_theorem() {
+        let square = [(0, 0), (4, 0), (4, 4), (0, 4)];
+        assert_eq!(lattice_boundary_points(&square), 16);
+        assert_eq!(lattice_interior_points(&square), 9);
+        assert_eq!(lattice_points_covered(&square), 25);
+
+        let triangle = [(0, 0), (4, 0), (0, 2)];
+        assert_eq!(lattice_boundary_points(&triangle), 8);
+        assert_eq!(lattice_interior_points(&triangle), 1);
+
+        // Same count as filling the sample in on a grid
+        assert_eq!(lattice_points_covered(&SAMPLE), 46);
+    }
+
+    #[test]
+    fn test_picks_theorem_flat_outline() {
+        // Walking a segment there and back has no area, and must not give a negative count
+        let segment = [(0, 0), (4, 2)];
+        assert_eq!(lattice_interior_points(&segment), 0);
+        assert_eq!(lattice_points_covered(&segment), 3);
+
+        let collinear = [(0, 0), (6, 0), (3, 0)];
+        assert_eq!(lattice_interior_points(&collinear), 0);
+        assert_eq!(lattice_points_covered(&collinear), 7);
+
+        assert_eq!(lattice_points_covered(&[(5, 5)]), 1);
+        assert_eq!(lattice_points_covered(&[]), 0);
+        assert_eq!(lattice_interior_points(&[]), 0);
+    }
+
+    #[test]
+    fn test_point_in_polygon() {
+        let triangle = [(0, 0), (4, 0), (0, 4)];
+        assert_eq!(point_in_polygon(&triangle, (1, 1)), PointLocation::Inside);
+        assert_eq!(
+            point_in_polygon(&triangle, (2, 2)),
+            PointLocation::OnBoundary
+        );
+        assert_eq!(
+            point_in_polygon(&triangle, (0, 3)),
+            PointLocation::OnBoundary
+        );
+        assert_eq!(point_in_polygon(&triangle, (3, 3)), PointLocation::Outside);
+        // Level with a vertex
+        assert_eq!(point_in_polygon(&triangle, (-1, 0)), PointLocation::Outside);
+        assert_eq!(point_in_polygon(&triangle, (-1, 4)), PointLocation::Outside);
+    }
+
+    #[test]
+    fn test_point_in_rectilinear_polygon_matches_general() {
+        for x in 0..14 {
+            for y in 0..9 {
+                assert_eq!(
+                    point_in_rectilinear_polygon(&SAMPLE, (x, y)),
+                    point_in_polygon(&SAMPLE, (x, y)),
+                    "{x},{y}"
+                );
+            }
+        }
+        assert_eq!(
+            point_in_rectilinear_polygon(&SAMPLE, (8, 4)),
+            PointLocation::Inside
+        );
+        assert_eq!(
+            point_in_rectilinear_polygon(&SAMPLE, (5, 3)),
+            PointLocation::OnBoundary
+        );
+        // In the notch, level with the notch's corners
+        assert_eq!(
+            point_in_rectilinear_polygon(&SAMPLE, (3, 1)),
+            PointLocation::Outside
+        );
+        assert_eq!(
+            point_in_rectilinear_polygon(&SAMPLE, (8, 7)),
+            PointLocation::Outside
+        );
+    }
+}
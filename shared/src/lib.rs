@@ -1,31 +1,61 @@
 mod array2d;
 mod array3d;
+mod array_render;
+mod array_transform;
 mod array_trim;
+mod automaton;
+mod bit_grid;
 mod challenges;
 mod combos;
+mod coordinate_compression;
+mod cycle_detection;
 mod decimal_digits_iter;
+mod distance_map;
 mod flood_fill;
 mod graph;
 mod input_files;
+mod kd_tree;
+mod kernel;
+mod largest_rectangle;
+mod lattice_polygon;
 mod maze;
+mod pattern_search;
 mod polygons;
+mod rasterise;
+mod raycast;
 mod read_input_file;
+mod regions;
 mod space_2d;
 mod space_3d;
 
 // Exports
 
+pub use self::array_render::*;
+pub use self::array_transform::*;
 pub use self::array_trim::*;
 pub use self::array2d::*;
 pub use self::array3d::*;
+pub use self::automaton::*;
+pub use self::bit_grid::*;
 pub use self::challenges::*;
 pub use self::combos::*;
+pub use self::coordinate_compression::*;
+pub use self::cycle_detection::*;
 pub use self::decimal_digits_iter::*;
+pub use self::distance_map::*;
 pub use self::flood_fill::*;
 pub use self::graph::*;
 pub use self::input_files::*;
+pub use self::kd_tree::*;
+pub use self::kernel::*;
+pub use self::largest_rectangle::*;
+pub use self::lattice_polygon::*;
 pub use self::maze::*;
+pub use self::pattern_search::*;
 pub use self::polygons::*;
+pub use self::rasterise::*;
+pub use self::raycast::*;
 pub use self::read_input_file::*;
+pub use self::regions::*;
 pub use self::space_2d::*;
 pub use self::space_3d::*;
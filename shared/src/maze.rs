@@ -1,65 +1,268 @@
-use crate::{Adjacents, adjacent_positions};
+use crate::{
+    Adjacents, ArrayPosition, Directions, adjacent_positions_iter, move_grid_cursor_by_direction,
+};
 use array2d::Array2D;
-use pathfinding::prelude::dijkstra;
-use std::collections::HashMap;
+use pathfinding::prelude::{astar, dijkstra};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashSet};
+use std::hash::Hash;
+
+/// Dijkstra's algorithm over any search state, i.e. a position plus a facing, or a count of steps taken in a straight line
+/// `successors` returns the states reachable from a state along with the cost to move there
+/// Returns the path of states from start to the first state accepted by `success`, and its total cost
+pub fn grid_search<S, FN, IN, FS>(start: &S, successors: FN, success: FS) -> Option<(Vec<S>, u32)>
+where
+    S: Clone + Eq + Hash,
+    FN: FnMut(&S) -> IN,
+    IN: IntoIterator<Item = (S, u32)>,
+    FS: FnMut(&S) -> bool,
+{
+    dijkstra(start, successors, success)
+}
 
 /// Walk the maze from start to end using Dijkstra's algorithm. Avoiding walls and using a cost function for tile weights
+/// Returns None if the end can't be reached
 pub fn maze_shortest_path<F>(
     maze: &Array2D<u8>,
     start: (usize, usize),
     end: (usize, usize),
     wall: u8,
     cost_fn: F,
-) -> Vec<(usize, usize)>
+) -> Option<Vec<(usize, usize)>>
 where
     F: Fn(u8) -> u32,
 {
-    // Create a weighted graph using adjacency list
-    // Each node maps to a list of (neighbor, weight) pairs
-    let row_col_to_index = |(row, col)| -> u32 { (row * maze.num_columns() + col) as u32 };
-
-    let graph: HashMap<u32, Vec<(u32, u32)>> = maze
-        .enumerate_row_major()
-        .filter(|(_, value)| *value != &wall)
-        .map(|(pos, _value)| {
-            // Check all of the surrounding positions
-            let adjacent_positions = adjacent_positions(maze, pos, Adjacents::CROSS);
-            let mut edges = Vec::new();
-            for adjacent_position in adjacent_positions {
-                let adjacent_cell_value =
-                    *maze.get(adjacent_position.0, adjacent_position.1).unwrap();
-                if adjacent_cell_value != wall {
-                    let cost = cost_fn(adjacent_cell_value);
-                    edges.push((row_col_to_index(adjacent_position), cost));
+    let successors = |pos: &ArrayPosition| {
+        adjacent_positions_iter(maze, *pos, Adjacents::CROSS)
+            .filter(|next| maze[*next] != wall)
+            .map(|next| (next, cost_fn(maze[next])))
+            .collect::<Vec<_>>()
+    };
+    let (path, _cost) = grid_search(&start, successors, |pos| *pos == end)?;
+    Some(path)
+}
+
+/// Search state for walking a maze where the direction being faced matters
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FacingState {
+    pub pos: ArrayPosition,
+    pub facing: Directions,
+}
+
+/// Shortest path through the maze where each step forward costs `step_cost` and each 90 degree turn on the spot costs `turn_cost`
+/// The end is reached when standing on it facing any direction
+pub fn maze_shortest_path_with_turns(
+    maze: &Array2D<u8>,
+    start: FacingState,
+    end: ArrayPosition,
+    wall: u8,
+    step_cost: u32,
+    turn_cost: u32,
+) -> Option<(Vec<FacingState>, u32)> {
+    let successors = |state: &FacingState| {
+        let mut next_states = vec![
+            (
+                FacingState {
+                    pos: state.pos,
+                    facing: state.facing.clockwise(),
+                },
+                turn_cost,
+            ),
+            (
+                FacingState {
+                    pos: state.pos,
+                    facing: state.facing.counterclockwise(),
+                },
+                turn_cost,
+            ),
+        ];
+        if let Some(pos) = move_grid_cursor_by_direction(maze, state.facing, state.pos)
+            && maze[pos] != wall
+        {
+            next_states.push((
+                FacingState {
+                    pos,
+                    facing: state.facing,
+                },
+                step_cost,
+            ));
+        }
+        next_states
+    };
+    grid_search(&start, successors, |state| state.pos == end)
+}
+
+/// The open cells of a maze and the cost of moving between them, built once so it can be searched many times
+/// Costs must be greater than zero
+#[derive(Debug, Clone)]
+pub struct MazeGraph {
+    num_columns: usize,
+    // Indexed by row * num_columns + col, holds (neighbour index, cost to enter neighbour)
+    edges: Vec<Vec<(usize, u32)>>,
+    min_cost: u32,
+}
+
+/// Result of `MazeGraph::all_shortest_paths`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShortestPaths {
+    pub cost: u32,
+    /// Every cell that lies on at least one shortest path, including start and end
+    pub cells: HashSet<ArrayPosition>,
+    /// Number of distinct shortest paths, saturating at u64::MAX
+    pub path_count: u64,
+}
+
+impl MazeGraph {
+    pub fn new<F>(maze: &Array2D<u8>, wall: u8, cost_fn: F) -> Self
+    where
+        F: Fn(u8) -> u32,
+    {
+        let mut min_cost = u32::MAX;
+        let edges = maze
+            .enumerate_row_major()
+            .map(|(pos, value)| {
+                if *value == wall {
+                    return vec![];
+                }
+                adjacent_positions_iter(maze, pos, Adjacents::CROSS)
+                    .filter(|next| maze[*next] != wall)
+                    .map(|next| {
+                        let cost = cost_fn(maze[next]);
+                        min_cost = min_cost.min(cost);
+                        (next.0 * maze.num_columns() + next.1, cost)
+                    })
+                    .collect()
+            })
+            .collect();
+        MazeGraph {
+            num_columns: maze.num_columns(),
+            edges,
+            min_cost,
+        }
+    }
+
+    fn index_of(&self, pos: ArrayPosition) -> usize {
+        pos.0 * self.num_columns + pos.1
+    }
+    fn position_of(&self, index: usize) -> ArrayPosition {
+        (index / self.num_columns, index % self.num_columns)
+    }
+
+    /// The open cells next to pos, with the cost of moving into them
+    pub fn neighbours(&self, pos: ArrayPosition) -> impl Iterator<Item = (ArrayPosition, u32)> {
+        self.edges[self.index_of(pos)]
+            .iter()
+            .map(|(index, cost)| (self.position_of(*index), *cost))
+    }
+
+    /// A* search using the Manhattan distance (scaled by the cheapest move) as the heuristic
+    pub fn shortest_path(
+        &self,
+        start: ArrayPosition,
+        end: ArrayPosition,
+    ) -> Option<(Vec<ArrayPosition>, u32)> {
+        let heuristic = |pos: &ArrayPosition| {
+            (pos.0.abs_diff(end.0) + pos.1.abs_diff(end.1)) as u32 * self.min_cost
+        };
+        astar(
+            &start,
+            |pos| self.neighbours(*pos),
+            heuristic,
+            |pos| *pos == end,
+        )
+    }
+
+    /// Find all of the cells that are on any shortest path from start to end, and how many shortest paths there are
+    pub fn all_shortest_paths(
+        &self,
+        start: ArrayPosition,
+        end: ArrayPosition,
+    ) -> Option<ShortestPaths> {
+        // Dijkstra, but keeping every predecessor that reaches a node at its best cost
+        let (start, end) = (self.index_of(start), self.index_of(end));
+        let mut best_cost = vec![u32::MAX; self.edges.len()];
+        let mut path_counts = vec![0u64; self.edges.len()];
+        let mut predecessors: Vec<Vec<usize>> = vec![vec![]; self.edges.len()];
+        let mut done = vec![false; self.edges.len()];
+        let mut queue = BinaryHeap::from([Reverse((0, start))]);
+        best_cost[start] = 0;
+        path_counts[start] = 1;
+
+        while let Some(Reverse((cost, node))) = queue.pop() {
+            if done[node] {
+                continue;
+            }
+            if cost > best_cost[end] {
+                break;
+            }
+            done[node] = true;
+            for (next, step_cost) in &self.edges[node] {
+                let next_cost = cost + step_cost;
+                if next_cost < best_cost[*next] {
+                    best_cost[*next] = next_cost;
+                    path_counts[*next] = path_counts[node];
+                    predecessors[*next] = vec![node];
+                    queue.push(Reverse((next_cost, *next)));
+                } else if next_cost == best_cost[*next] {
+                    path_counts[*next] = path_counts[*next].saturating_add(path_counts[node]);
+                    predecessors[*next].push(node);
                 }
             }
-            (row_col_to_index(pos), edges)
-        })
-        .collect();
-
-    // Define the successor function
-    let successors =
-        |node: &u32| -> Vec<(u32, u32)> { graph.get(node).cloned().unwrap_or_default() };
-
-    // Find shortest path from A to E using Dijkstra's algorithm
-    let (path, _cost) = dijkstra(&row_col_to_index(start), successors, |&node| {
-        node == row_col_to_index(end)
-    })
-    .unwrap();
-
-    // Convert path from node indices back to (row, col) coordinates
-    path.into_iter()
-        .map(|node| {
-            let row = (node as usize) / maze.num_columns();
-            let col = (node as usize) % maze.num_columns();
-            (row, col)
+        }
+        if best_cost[end] == u32::MAX {
+            return None;
+        }
+
+        // Walk the predecessors back from the end to collect the cells
+        let mut cells = HashSet::from([self.position_of(end)]);
+        let mut to_visit = vec![end];
+        while let Some(node) = to_visit.pop() {
+            for previous in &predecessors[node] {
+                if cells.insert(self.position_of(*previous)) {
+                    to_visit.push(*previous);
+                }
+            }
+        }
+        Some(ShortestPaths {
+            cost: best_cost[end],
+            cells,
+            path_count: path_counts[end],
         })
-        .collect()
+    }
+}
+
+/// A* version of `maze_shortest_path`, also returning the cost of the path
+pub fn maze_shortest_path_astar<F>(
+    maze: &Array2D<u8>,
+    start: ArrayPosition,
+    end: ArrayPosition,
+    wall: u8,
+    cost_fn: F,
+) -> Option<(Vec<ArrayPosition>, u32)>
+where
+    F: Fn(u8) -> u32,
+{
+    MazeGraph::new(maze, wall, cost_fn).shortest_path(start, end)
+}
+
+/// Every cell on any shortest path through the maze, and the number of distinct shortest paths
+pub fn maze_all_shortest_paths<F>(
+    maze: &Array2D<u8>,
+    start: ArrayPosition,
+    end: ArrayPosition,
+    wall: u8,
+    cost_fn: F,
+) -> Option<ShortestPaths>
+where
+    F: Fn(u8) -> u32,
+{
+    MazeGraph::new(maze, wall, cost_fn).all_shortest_paths(start, end)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::array2d::grid_from_lines;
 
     #[test]
     fn test_maze_shortest_path_10x10() {
@@ -91,7 +294,7 @@ mod tests {
         let wall = 1u8;
 
         // Find the shortest path
-        let path = maze_shortest_path(&maze, start, end, wall, |_| 10);
+        let path = maze_shortest_path(&maze, start, end, wall, |_| 10).unwrap();
 
         // Verify the path
         assert!(!path.is_empty(), "Path should not be empty");
@@ -136,4 +339,156 @@ mod tests {
             path.len()
         );
     }
+
+    #[test]
+    fn test_maze_shortest_path_no_path() {
+        let maze = Array2D::from_rows(&[vec![0u8, 1, 0]]).unwrap();
+        assert_eq!(maze_shortest_path(&maze, (0, 0), (0, 2), 1, |_| 1), None);
+    }
+
+    #[test]
+    fn test_maze_shortest_path_with_turns() {
+        // A short staircase with lots of turns vs a long way round with few turns
+        let maze = grid_from_lines(&[
+            "####......#",
+            "#.........#",
+            "#..######.#",
+            "##..#####.#",
+            "###..####.#",
+            "####......#",
+            "####......#",
+        ]);
+        let start = FacingState {
+            pos: (2, 1),
+            facing: Directions::RIGHT,
+        };
+        let end = (5, 6);
+        // With cheap turns, take the staircase: 8 steps with 6 turns
+        let (path, cost) = maze_shortest_path_with_turns(&maze, start, end, b'#', 1, 1).unwrap();
+        assert_eq!(cost, 14);
+        assert_eq!(path[0], start);
+        assert_eq!(path.last().unwrap().pos, end);
+
+        // With expensive turns, go the long way round: 16 steps with 4 turns
+        let (_, cost) = maze_shortest_path_with_turns(&maze, start, end, b'#', 1, 1000).unwrap();
+        assert_eq!(cost, 4016);
+    }
+
+    // Search state for a cart that can only take at most 2 steps in a row in the same direction
+    #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    struct MomentumState {
+        pos: ArrayPosition,
+        facing: Directions,
+        run: u8,
+    }
+    fn momentum_successors(maze: &Array2D<u8>, state: &MomentumState) -> Vec<(MomentumState, u32)> {
+        [
+            state.facing,
+            state.facing.clockwise(),
+            state.facing.counterclockwise(),
+        ]
+        .into_iter()
+        .filter_map(|facing| {
+            let run = if facing == state.facing {
+                state.run + 1
+            } else {
+                1
+            };
+            let pos = move_grid_cursor_by_direction(maze, facing, state.pos)?;
+            (run <= 2).then_some((MomentumState { pos, facing, run }, 1))
+        })
+        .collect()
+    }
+
+    #[test]
+    fn test_grid_search_momentum() {
+        let start = MomentumState {
+            pos: (0, 0),
+            facing: Directions::RIGHT,
+            run: 0,
+        };
+        // A single row corridor can't be crossed without turning
+        let maze = grid_from_lines(&["......"]);
+        let result = grid_search(
+            &start,
+            |state| momentum_successors(&maze, state),
+            |state| state.pos == (0, 5),
+        );
+        assert!(result.is_none());
+
+        // With a second row there is room to weave
+        let maze = grid_from_lines(&["......", "......"]);
+        let (path, cost) = grid_search(
+            &start,
+            |state| momentum_successors(&maze, state),
+            |state| state.pos == (0, 5),
+        )
+        .unwrap();
+        // 5 steps right plus a step down and back up again to break up the run
+        assert_eq!(cost, 7);
+        assert!(path.iter().all(|state| state.run <= 2));
+    }
+
+    #[test]
+    fn test_maze_shortest_path_astar_matches_dijkstra() {
+        let maze = grid_from_lines(&[
+            "..........",
+            ".########.",
+            ".#......#.",
+            ".#.####.#.",
+            "...#..#...",
+            "##.#.##.##",
+            "...#......",
+        ]);
+        let cost_fn = |value: u8| if value == b'.' { 2 } else { 5 };
+        let graph = MazeGraph::new(&maze, b'#', cost_fn);
+        for end in [(6, 0), (6, 9), (2, 4), (4, 4)] {
+            let dijkstra_path = maze_shortest_path(&maze, (0, 0), end, b'#', cost_fn);
+            let astar_result = graph.shortest_path((0, 0), end);
+            match (dijkstra_path, astar_result) {
+                (Some(dijkstra_path), Some((astar_path, cost))) => {
+                    assert_eq!(dijkstra_path.len(), astar_path.len());
+                    assert_eq!(cost, 2 * (astar_path.len() as u32 - 1));
+                }
+                (None, None) => {}
+                other => panic!("Searches disagree for {end:?}: {other:?}"),
+            }
+        }
+        let walled_in = grid_from_lines(&["...", ".#.", "#.#"]);
+        assert_eq!(
+            maze_shortest_path_astar(&walled_in, (0, 0), (2, 1), b'#', cost_fn),
+            None
+        );
+    }
+
+    #[test]
+    fn test_maze_all_shortest_paths() {
+        let maze = grid_from_lines(&["...", ".#.", "..."]);
+        let result = maze_all_shortest_paths(&maze, (0, 0), (2, 2), b'#', |_| 1).unwrap();
+        assert_eq!(result.cost, 4);
+        assert_eq!(result.path_count, 2);
+        assert_eq!(result.cells.len(), 8);
+        assert!(!result.cells.contains(&(1, 1)));
+
+        // Open 3x3 grid has 6 monotone paths corner to corner, using every cell
+        let maze = grid_from_lines(&["...", "...", "..."]);
+        let result = maze_all_shortest_paths(&maze, (0, 0), (2, 2), b'#', |_| 1).unwrap();
+        assert_eq!(result.path_count, 6);
+        assert_eq!(result.cells.len(), 9);
+
+        // Weighted cells steer every shortest path around the slow cell
+        let maze = grid_from_lines(&["...", ".9.", "..."]);
+        let result = maze_all_shortest_paths(&maze, (1, 0), (1, 2), b'#', |value| {
+            if value == b'9' { 9 } else { 1 }
+        })
+        .unwrap();
+        assert_eq!(result.cost, 4);
+        assert_eq!(result.path_count, 2);
+        assert!(!result.cells.contains(&(1, 1)));
+
+        assert_eq!(
+            maze_all_shortest_paths(&grid_from_lines(&[".#."]), (0, 0), (0, 2), b'#', |_| 1),
+            None
+        );
+    }
 }
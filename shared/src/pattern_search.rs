@@ -0,0 +1,132 @@
+use array2d::Array2D;
+
+use crate::{ArrayPosition, Orientation, unique_orientations};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PatternMatch {
+    /// Where the top left of the oriented pattern sits in the grid
+    pub pos: ArrayPosition,
+    pub orientation: Orientation,
+}
+
+/// Does the pattern match the grid with its top left corner at pos
+/// Pattern cells holding the wildcard match anything
+pub fn pattern_matches_at<T: PartialEq>(
+    grid: &Array2D<T>,
+    pattern: &Array2D<T>,
+    pos: ArrayPosition,
+    wildcard: Option<&T>,
+) -> bool {
+    if pos.0 + pattern.num_rows() > grid.num_rows()
+        || pos.1 + pattern.num_columns() > grid.num_columns()
+    {
+        return false;
+    }
+    pattern.enumerate_row_major().all(|((row, col), value)| {
+        Some(value) == wildcard || *value == grid[(pos.0 + row, pos.1 + col)]
+    })
+}
+
+/// All the positions (top left corner) where the pattern appears in the grid, in row major order
+/// Pattern cells holding the wildcard match anything, so it can be used for transparent cells
+pub fn find_pattern<T: PartialEq>(
+    grid: &Array2D<T>,
+    pattern: &Array2D<T>,
+    wildcard: Option<&T>,
+) -> Vec<ArrayPosition> {
+    if pattern.num_rows() > grid.num_rows() || pattern.num_columns() > grid.num_columns() {
+        return vec![];
+    }
+    let last_row = grid.num_rows() - pattern.num_rows();
+    let last_col = grid.num_columns() - pattern.num_columns();
+    (0..=last_row)
+        .flat_map(|row| (0..=last_col).map(move |col| (row, col)))
+        .filter(|pos| pattern_matches_at(grid, pattern, *pos, wildcard))
+        .collect()
+}
+
+/// Same as `find_pattern` but tries every distinct rotation and flip of the pattern
+/// Symmetric patterns only report each placement once
+pub fn find_pattern_any_orientation<T: Clone + PartialEq>(
+    grid: &Array2D<T>,
+    pattern: &Array2D<T>,
+    wildcard: Option<&T>,
+) -> Vec<PatternMatch> {
+    unique_orientations(pattern)
+        .flat_map(|(orientation, oriented)| {
+            find_pattern(grid, &oriented, wildcard)
+                .into_iter()
+                .map(move |pos| PatternMatch { pos, orientation })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::array2d::grid_from_lines;
+
+    #[test]
+    fn test_find_pattern() {
+        let grid = grid_from_lines(&["abab", "baba", "abab"]);
+        let pattern = grid_from_lines(&["ab", "ba"]);
+        assert_eq!(
+            find_pattern(&grid, &pattern, None),
+            vec![(0, 0), (0, 2), (1, 1)]
+        );
+
+        let too_big = grid_from_lines(&["abababa"]);
+        assert!(find_pattern(&grid, &too_big, None).is_empty());
+    }
+
+    #[test]
+    fn test_find_pattern_wildcard() {
+        let grid = grid_from_lines(&["##..", "###.", "..##"]);
+        // An L shape, the . cells can be anything
+        let pattern = grid_from_lines(&["#.", "##"]);
+        assert_eq!(find_pattern(&grid, &pattern, None), vec![(0, 1), (1, 2)]);
+        assert_eq!(
+            find_pattern(&grid, &pattern, Some(&b'.')),
+            vec![(0, 0), (0, 1), (1, 2)]
+        );
+    }
+
+    #[test]
+    fn test_find_pattern_any_orientation() {
+        let grid = grid_from_lines(&["##..", "#...", "...#", "..##"]);
+        let pattern = grid_from_lines(&["#.", "##"]);
+        let matches = find_pattern_any_orientation(&grid, &pattern, Some(&b'.'));
+        assert_eq!(matches.len(), 2);
+        for found in matches {
+            let oriented = found.orientation.apply(&pattern);
+            assert!(pattern_matches_at(&grid, &oriented, found.pos, Some(&b'.')));
+        }
+
+        let full = Array2D::filled_with(b'#', 3, 3);
+        assert!(find_pattern_any_orientation(&full, &pattern, None).is_empty());
+        assert_eq!(
+            find_pattern_any_orientation(&full, &pattern, Some(&b'.')).len(),
+            16
+        );
+    }
+
+    #[test]
+    fn test_find_pattern_symmetric_reported_once() {
+        let grid = grid_from_lines(&["###", "###"]);
+        let pattern = grid_from_lines(&["##", "##"]);
+        let matches = find_pattern_any_orientation(&grid, &pattern, None);
+        assert_eq!(
+            matches,
+            vec![
+                PatternMatch {
+                    pos: (0, 0),
+                    orientation: Orientation::Identity
+                },
+                PatternMatch {
+                    pos: (0, 1),
+                    orientation: Orientation::Identity
+                },
+            ]
+        );
+    }
+}
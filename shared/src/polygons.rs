@@ -1,122 +1,213 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
 use array2d::Array2D;
 
-pub fn infill_poly(data: &mut Array2D<u8>, point_marker: u8) {
-    // Use scanline algorithm with even-odd rule to fill polygon and handle holes
-    let rows = data.num_rows();
-    let cols = data.num_columns();
+use crate::{Adjacents, ArrayPosition, adjacent_positions_iter, flood_region};
 
-    for y in 0..rows {
-        // Find all boundary crossings on this scanline
-        let mut raw_crossings = Vec::new();
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FillRule {
+    /// Inside if a ray out of the shape crosses the outline an odd number of times
+    EvenOdd,
+    /// Inside if the outline winds around the cell at all, so loops that overlap themselves fill solid
+    NonZero,
+}
 
-        for x in 0..cols {
-            if *data.get(y, x).unwrap() == point_marker {
-                raw_crossings.push(x);
-            }
-        }
+/// Fill in the inside of outlines drawn with `point_marker` using the even-odd rule
+pub fn infill_poly(data: &mut Array2D<u8>, point_marker: u8) {
+    infill_poly_with_rule(data, point_marker, FillRule::EvenOdd);
+}
 
-        // Consolidate consecutive boundary pixels into pairs of crossings (start and end)
-        let mut crossings = Vec::new();
-        let mut i = 0;
-        while i < raw_crossings.len() {
-            let start = raw_crossings[i];
-            let mut end = start;
-
-            // Find the end of this consecutive sequence
-            while i + 1 < raw_crossings.len() && raw_crossings[i + 1] == raw_crossings[i] + 1 {
-                i += 1;
-                end = raw_crossings[i];
+/// Fill in the inside of closed outlines (1 cell thick, joined through cell edges) drawn with `point_marker`
+/// Each row is scanned just below the cell centres, so a run of outline cells only counts as a crossing
+/// through the links down to the row below. A horizontal edge whose ends both turn the same way is not
+/// crossed, and a row that never gets back outside (outline with a gap) is left unfilled
+pub fn infill_poly_with_rule(data: &mut Array2D<u8>, point_marker: u8, rule: FillRule) {
+    let outline = data.clone();
+    let is_marker = |pos: ArrayPosition| outline.get(pos.0, pos.1) == Some(&point_marker);
+    let link_direction = match rule {
+        FillRule::EvenOdd => HashMap::new(),
+        FillRule::NonZero => trace_vertical_links(&outline, point_marker),
+    };
+
+    let mut pending = Vec::new();
+    for row in 0..outline.num_rows() {
+        let mut winding = 0;
+        pending.clear();
+        let mut col = 0;
+        while col < outline.num_columns() {
+            if !is_marker((row, col)) {
+                if winding != 0 {
+                    pending.push(col);
+                }
+                col += 1;
+                continue;
             }
-
-            // For single pixels, add one crossing. For thick edges, add start and end
-            if start == end {
-                crossings.push(start);
-            } else {
-                crossings.push(start);
-                crossings.push(end);
+            // Hitting a run of outline means everything pending was inside
+            for fill_col in pending.drain(..) {
+                data[(row, fill_col)] = point_marker;
             }
-            i += 1;
-        }
-
-        // Fill between pairs of crossings using even-odd rule
-        for chunk in crossings.chunks(2) {
-            if chunk.len() == 2 {
-                let start = chunk[0];
-                let end = chunk[1];
-
-                // Fill the area between crossings (exclusive of boundaries)
-                for x in (start + 1)..end {
-                    data.set(y, x, point_marker).unwrap();
+            while col < outline.num_columns() && is_marker((row, col)) {
+                if is_marker((row + 1, col)) {
+                    winding = match rule {
+                        FillRule::EvenOdd => 1 - winding,
+                        FillRule::NonZero => winding + link_direction[&(row, col)],
+                    };
                 }
+                col += 1;
             }
         }
+    }
+}
 
-        // Handle odd number of crossings - fill to the end
-        if crossings.len() % 2 == 1
-            && let Some(&last_crossing) = crossings.last()
-        {
-            for x in (last_crossing + 1)..cols {
-                data.set(y, x, point_marker).unwrap();
+// Walk each outline, returning the direction it passes through every vertical link: +1 down, -1 up
+// Keyed by the upper cell of the link. Where outlines cross, the walk carries straight on
+fn trace_vertical_links(outline: &Array2D<u8>, point_marker: u8) -> HashMap<ArrayPosition, i32> {
+    let mut used_links = HashSet::new();
+    let mut directions = HashMap::new();
+    for (start, value) in outline.enumerate_row_major() {
+        if *value != point_marker {
+            continue;
+        }
+        let mut pos = start;
+        let mut heading = None;
+        loop {
+            let unused: Vec<ArrayPosition> =
+                adjacent_positions_iter(outline, pos, Adjacents::CROSS)
+                    .filter(|next| outline[*next] == point_marker)
+                    .filter(|next| !used_links.contains(&(pos.min(*next), pos.max(*next))))
+                    .collect();
+            let straight_on = heading.and_then(|(dr, dc): (i64, i64)| {
+                let ahead = (pos.0 as i64 + dr, pos.1 as i64 + dc);
+                unused
+                    .iter()
+                    .find(|next| (next.0 as i64, next.1 as i64) == ahead)
+            });
+            let Some(next) = straight_on.or(unused.first()).copied() else {
+                break;
+            };
+            used_links.insert((pos.min(next), pos.max(next)));
+            if next.0 != pos.0 {
+                let sign = if next.0 > pos.0 { 1 } else { -1 };
+                directions.insert(pos.min(next), sign);
             }
+            heading = Some((next.0 as i64 - pos.0 as i64, next.1 as i64 - pos.1 as i64));
+            pos = next;
         }
     }
+    directions
 }
 
-/// Given a grid that has points marked with `point_marker`,
-/// Find these points and return a polygon defined by them.
+/// Trace the outline of the region of `point_marker` cells containing the first marker found (row major)
+/// Each cell is treated as a unit square, so the polygon's vertices are cell corners with x = col and y = row,
+/// and the ring runs clockwise on screen (y pointing down). Holes inside the region become interior rings.
+/// Cells only join the region through their edges, other regions are ignored (see `label_regions`)
 pub fn find_polygon(data: &Array2D<u8>, point_marker: u8) -> geo::Polygon<f64> {
-    // Walk the array, and find all the points that have point marker and return the polygon defined by those points
-    let mut points = Vec::new();
-
-    for y in 0..data.num_rows() {
-        for x in 0..data.num_columns() {
-            if *data.get(y, x).unwrap() == point_marker {
-                points.push(geo::Coord {
-                    x: y as f64,
-                    y: x as f64,
-                });
+    let Some((start, _)) = data
+        .enumerate_row_major()
+        .find(|(_, value)| **value == point_marker)
+    else {
+        return geo::Polygon::new(geo::LineString::new(vec![]), vec![]);
+    };
+    let region = flood_region(data, start, Adjacents::CROSS, |value| {
+        *value == point_marker
+    })
+    .cells;
+
+    // Every cell side that faces out of the region, directed so the region is on the right
+    let mut edges: BTreeMap<ArrayPosition, Vec<ArrayPosition>> = BTreeMap::new();
+    for &(row, col) in &region {
+        let outside = |dr: i64, dc: i64| {
+            let neighbour = (row as i64 + dr, col as i64 + dc);
+            neighbour.0 < 0
+                || neighbour.1 < 0
+                || !region.contains(&(neighbour.0 as usize, neighbour.1 as usize))
+        };
+        let (top_left, top_right) = ((row, col), (row, col + 1));
+        let (bottom_left, bottom_right) = ((row + 1, col), (row + 1, col + 1));
+        for (is_edge, from, to) in [
+            (outside(-1, 0), top_left, top_right),
+            (outside(0, 1), top_right, bottom_right),
+            (outside(1, 0), bottom_right, bottom_left),
+            (outside(0, -1), bottom_left, top_left),
+        ] {
+            if is_edge {
+                edges.entry(from).or_default().push(to);
             }
         }
     }
 
-    // Sort points using Graham scan approach for proper polygon ordering
-    if points.len() > 2 {
-        // Find the bottommost point (largest x), then rightmost if tied (largest y)
-        let mut start = 0;
-        for i in 1..points.len() {
-            if points[i].x > points[start].x
-                || (points[i].x == points[start].x && points[i].y > points[start].y)
-            {
-                start = i;
-            }
-        }
+    // The top left corner of the first cell is always on the outside
+    let exterior = trace_ring(&mut edges, start);
+    let mut holes = Vec::new();
+    while let Some(hole_start) = edges.keys().next().copied() {
+        holes.push(trace_ring(&mut edges, hole_start));
+    }
+    geo::Polygon::new(exterior, holes)
+}
 
-        // Move the start point to the beginning
-        points.swap(0, start);
-        let start_point = points[0];
-
-        // Sort remaining points by polar angle with respect to start point
-        points[1..].sort_by(|a, b| {
-            let cross_product = (a.x - start_point.x) * (b.y - start_point.y)
-                - (a.y - start_point.y) * (b.x - start_point.x);
-
-            if cross_product == 0.0 {
-                // Collinear points - sort by distance
-                let dist_a = (a.x - start_point.x).powi(2) + (a.y - start_point.y).powi(2);
-                let dist_b = (b.x - start_point.x).powi(2) + (b.y - start_point.y).powi(2);
-                dist_a.partial_cmp(&dist_b).unwrap()
-            } else {
-                cross_product.partial_cmp(&0.0).unwrap().reverse()
+// Follow the edges around from start until back at start, consuming them as we go
+// Where two edges leave a corner (cells touching diagonally) take the left turn, away from the region,
+// so the rings either side of the touching corner stay separate and each ring is simple
+fn trace_ring(
+    edges: &mut BTreeMap<ArrayPosition, Vec<ArrayPosition>>,
+    start: ArrayPosition,
+) -> geo::LineString<f64> {
+    let mut corners = Vec::new();
+    let mut pos = start;
+    let mut heading: Option<(i64, i64)> = None;
+    loop {
+        let outgoing = edges.get_mut(&pos).unwrap();
+        let index = match heading {
+            Some((dr, dc)) if outgoing.len() > 1 => {
+                let left = (-dc, dr);
+                outgoing
+                    .iter()
+                    .position(|next| {
+                        (next.0 as i64 - pos.0 as i64, next.1 as i64 - pos.1 as i64) == left
+                    })
+                    .unwrap_or(0)
             }
-        });
+            _ => 0,
+        };
+        let next = outgoing.swap_remove(index);
+        if outgoing.is_empty() {
+            edges.remove(&pos);
+        }
+        let direction = (next.0 as i64 - pos.0 as i64, next.1 as i64 - pos.1 as i64);
+        if heading != Some(direction) {
+            corners.push(pos);
+        }
+        heading = Some(direction);
+        pos = next;
+        if pos == start {
+            break;
+        }
     }
-
-    // Create a polygon from the collected points
-    geo::Polygon::new(geo::LineString::from(points), vec![])
+    // The start may have been part way along a straight edge
+    let first_heading = (
+        corners[1 % corners.len()].0 as i64 - corners[0].0 as i64,
+        corners[1 % corners.len()].1 as i64 - corners[0].1 as i64,
+    );
+    let last = corners[corners.len() - 1];
+    let closing = (
+        start.0 as i64 - last.0 as i64,
+        start.1 as i64 - last.1 as i64,
+    );
+    if corners.len() > 2 && first_heading.0 * closing.1 == first_heading.1 * closing.0 {
+        corners.remove(0);
+    }
+    corners
+        .into_iter()
+        .map(|(row, col)| geo::Coord {
+            x: col as f64,
+            y: row as f64,
+        })
+        .collect()
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::array2d::grid_from_lines;
     use crate::print_array;
 
     use super::*;
@@ -211,12 +302,8 @@ mod tests {
 
         infill_poly(&mut data, 2);
 
-        // Check result
-        assert_eq!(*data.get(0, 0).unwrap(), 0);
-        assert_eq!(*data.get(0, 1).unwrap(), 2);
-        assert_eq!(*data.get(0, 2).unwrap(), 2);
-        assert_eq!(*data.get(0, 3).unwrap(), 2);
-        assert_eq!(*data.get(0, 4).unwrap(), 0);
+        // Neither marker is part of an outline that crosses the row, so nothing is inside
+        assert_eq!(data.as_rows(), vec![vec![0, 2, 0, 2, 0]]);
     }
 
     #[test]
@@ -235,70 +322,223 @@ mod tests {
 
     #[test]
     fn test_infill_poly_odd_number_of_markers() {
-        let mut data = Array2D::filled_with(0u8, 1, 4);
+        let mut data = Array2D::filled_with(0u8, 2, 4);
 
-        // Set single marker
+        // Single crossing that never closes
         data.set(0, 1, 3).unwrap();
+        data.set(1, 1, 3).unwrap();
 
         infill_poly(&mut data, 3);
 
-        // Everything after the marker should be filled
-        assert_eq!(*data.get(0, 0).unwrap(), 0);
-        assert_eq!(*data.get(0, 1).unwrap(), 3);
-        assert_eq!(*data.get(0, 2).unwrap(), 3);
-        assert_eq!(*data.get(0, 3).unwrap(), 3);
+        // Nothing after the marker should be filled
+        assert_eq!(data.as_rows(), vec![vec![0, 3, 0, 0], vec![0, 3, 0, 0]]);
     }
 
     #[test]
-    fn test_find_polygon() {
-        let mut data = Array2D::filled_with(b'.', 10, 10);
-        // Draw a triangle
-        data.set(1, 1, b'#').unwrap();
-        data.set(1, 5, b'#').unwrap();
-        data.set(5, 3, b'#').unwrap();
-        let polygon = find_polygon(&data, b'#');
-        let expected_points = vec![(5, 3), (1, 5), (1, 1)];
-        let mut poly_points: Vec<(u8, u8)> = polygon
-            .exterior()
+    fn test_infill_poly_concave_u() {
+        // Horizontal edges at the bottom of the notch and the bottom of the arms both turn upwards
+        let mut data = grid_from_lines(&[
+            "#####.#####",
+            "#...#.#...#",
+            "#...###...#",
+            "#.........#",
+            "###########",
+        ]);
+        infill_poly(&mut data, b'#');
+        assert_eq!(
+            data,
+            grid_from_lines(&[
+                "#####.#####",
+                "#####.#####",
+                "###########",
+                "###########",
+                "###########",
+            ])
+        );
+    }
+
+    #[test]
+    fn test_infill_poly_upside_down_u() {
+        let mut data = grid_from_lines(&[
+            "...........",
+            ".#########.",
+            ".#.......#.",
+            ".#..###..#.",
+            ".#..#.#..#.",
+            ".####.####.",
+        ]);
+        infill_poly(&mut data, b'#');
+        assert_eq!(
+            data,
+            grid_from_lines(&[
+                "...........",
+                ".#########.",
+                ".#########.",
+                ".#########.",
+                ".####.####.",
+                ".####.####.",
+            ])
+        );
+    }
+
+    #[test]
+    fn test_infill_poly_fill_rules() {
+        // One outline that goes around the middle twice, crossing itself at (6, 2)
+        let vertices = [
+            (0, 0),
+            (0, 8),
+            (8, 8),
+            (8, 2),
+            (2, 2),
+            (2, 6),
+            (6, 6),
+            (6, 0),
+        ];
+        let mut outline = Array2D::filled_with(b'.', 9, 9);
+        crate::draw_rectilinear_edges(&mut outline, &vertices, b'#').unwrap();
+
+        let mut even_odd = outline.clone();
+        infill_poly_with_rule(&mut even_odd, b'#', FillRule::EvenOdd);
+        let mut non_zero = outline.clone();
+        infill_poly_with_rule(&mut non_zero, b'#', FillRule::NonZero);
+
+        // Wound around twice, so only non-zero fills the middle
+        assert_eq!(even_odd[(4, 4)], b'.');
+        assert_eq!(non_zero[(4, 4)], b'#');
+        // Wound around once
+        for pos in [(1, 1), (4, 1), (7, 4), (4, 7)] {
+            assert_eq!(even_odd[pos], b'#');
+            assert_eq!(non_zero[pos], b'#');
+        }
+        // Outside the outline
+        for pos in [(7, 0), (7, 1), (8, 0)] {
+            assert_eq!(even_odd[pos], b'.');
+            assert_eq!(non_zero[pos], b'.');
+        }
+    }
+
+    fn ring_points(ring: &geo::LineString<f64>) -> Vec<(i64, i64)> {
+        let mut points: Vec<(i64, i64)> = ring
             .points()
-            .map(|p| (p.x() as u8, p.y() as u8))
+            .map(|p| (p.x() as i64, p.y() as i64))
             .collect();
         // Remove the duplicate closing point that geo::LineString adds automatically
-        if poly_points.len() > 1 && poly_points[0] == poly_points[poly_points.len() - 1] {
-            poly_points.pop();
+        if points.len() > 1 && points[0] == points[points.len() - 1] {
+            points.pop();
         }
-        assert_eq!(poly_points, expected_points);
+        points
     }
+
     #[test]
-    fn test_find_polygon_indent() {
+    fn test_find_polygon() {
         let mut data = Array2D::filled_with(b'.', 10, 10);
-        // Draw a triangle, in indent
-        data.set(1, 1, b'#').unwrap();
-        data.set(1, 8, b'#').unwrap();
-        data.set(3, 5, b'#').unwrap();
-        data.set(8, 5, b'#').unwrap();
+        data.set(2, 3, b'#').unwrap();
         let polygon = find_polygon(&data, b'#');
-        for row in 0..data.num_rows() {
-            for col in 0..data.num_columns() {
-                let point = geo::Point::new(row as f64, col as f64);
-                if geo::Contains::contains(&polygon, &point) {
-                    print!("#");
-                } else {
-                    print!(".");
-                }
-            }
-            println!();
-        }
-        let expected_points = vec![(8, 5), (1, 8), (3, 5), (1, 1)];
-        let mut poly_points: Vec<(u8, u8)> = polygon
-            .exterior()
-            .points()
-            .map(|p| (p.x() as u8, p.y() as u8))
-            .collect();
-        // Remove the duplicate closing point that geo::LineString adds automatically
-        if poly_points.len() > 1 && poly_points[0] == poly_points[poly_points.len() - 1] {
-            poly_points.pop();
+        // Corners of the cell, (x, y) clockwise on screen from the top left
+        assert_eq!(
+            ring_points(polygon.exterior()),
+            vec![(3, 2), (4, 2), (4, 3), (3, 3)]
+        );
+        assert!(polygon.interiors().is_empty());
+
+        let empty = find_polygon(&Array2D::filled_with(b'.', 3, 3), b'#');
+        assert!(empty.exterior().0.is_empty());
+    }
+
+    #[test]
+    fn test_find_polygon_indent() {
+        let data = grid_from_lines(&[".#..#", ".#..#", ".####"]);
+        let polygon = find_polygon(&data, b'#');
+        assert_eq!(
+            ring_points(polygon.exterior()),
+            vec![
+                (1, 0),
+                (2, 0),
+                (2, 2),
+                (4, 2),
+                (4, 0),
+                (5, 0),
+                (5, 3),
+                (1, 3)
+            ]
+        );
+        assert_eq!(geo::Area::unsigned_area(&polygon), 8.0);
+        // Cell centres inside the notch are outside the polygon
+        assert!(!geo::Contains::contains(
+            &polygon,
+            &geo::Point::new(2.5, 0.5)
+        ));
+        assert!(geo::Contains::contains(
+            &polygon,
+            &geo::Point::new(1.5, 0.5)
+        ));
+        assert!(geo::Contains::contains(
+            &polygon,
+            &geo::Point::new(4.5, 2.5)
+        ));
+    }
+
+    #[test]
+    fn test_find_polygon_holes() {
+        let data = grid_from_lines(&["####", "#.##", "####", "##.#", "####"]);
+        let polygon = find_polygon(&data, b'#');
+        assert_eq!(
+            ring_points(polygon.exterior()),
+            vec![(0, 0), (4, 0), (4, 5), (0, 5)]
+        );
+        assert_eq!(polygon.interiors().len(), 2);
+        assert_eq!(
+            ring_points(&polygon.interiors()[0]),
+            vec![(1, 1), (1, 2), (2, 2), (2, 1)]
+        );
+        assert_eq!(geo::Area::unsigned_area(&polygon), 18.0);
+        assert!(!geo::Contains::contains(
+            &polygon,
+            &geo::Point::new(2.5, 3.5)
+        ));
+    }
+
+    #[test]
+    fn test_find_polygon_hole_touching_outside() {
+        // The hole only meets the outside (or the notch) at a corner, it must not fold into the exterior
+        for lines in [["###", "#.#", "##."], ["##.", "#.#", "###"]] {
+            let polygon = find_polygon(&grid_from_lines(&lines), b'#');
+            assert!(geo::Validation::is_valid(&polygon), "{lines:?}");
+            assert_eq!(polygon.interiors().len(), 1, "{lines:?}");
+            assert_eq!(
+                ring_points(&polygon.interiors()[0]),
+                vec![(1, 1), (1, 2), (2, 2), (2, 1)]
+            );
+            assert_eq!(geo::Area::unsigned_area(&polygon), 7.0);
         }
-        assert_eq!(poly_points, expected_points);
+        let polygon = find_polygon(&grid_from_lines(&["###", "#.#", "##."]), b'#');
+        assert_eq!(
+            ring_points(polygon.exterior()),
+            vec![(0, 0), (3, 0), (3, 2), (2, 2), (2, 3), (0, 3)]
+        );
+    }
+
+    #[test]
+    fn test_find_polygon_holes_touching() {
+        let data = grid_from_lines(&["####", "#.##", "##.#", "####"]);
+        let polygon = find_polygon(&data, b'#');
+        assert!(geo::Validation::is_valid(&polygon));
+        assert_eq!(polygon.interiors().len(), 2);
+        assert_eq!(
+            ring_points(&polygon.interiors()[1]),
+            vec![(2, 2), (2, 3), (3, 3), (3, 2)]
+        );
+        assert_eq!(geo::Area::unsigned_area(&polygon), 14.0);
+    }
+
+    #[test]
+    fn test_find_polygon_first_region_only() {
+        let data = grid_from_lines(&["##...", "#....", "...##"]);
+        let polygon = find_polygon(&data, b'#');
+        assert_eq!(geo::Area::unsigned_area(&polygon), 3.0);
+        assert_eq!(
+            ring_points(polygon.exterior()),
+            vec![(0, 0), (2, 0), (2, 1), (1, 1), (1, 2), (0, 2)]
+        );
     }
 }
@@ -0,0 +1,196 @@
+use array2d::Array2D;
+
+use crate::ArrayPosition;
+
+/// Draw a straight line of value from `from` to `to` (both included) using Bresenham's algorithm
+/// Cells that fall outside the grid are skipped
+pub fn draw_line<T: Clone>(
+    grid: &mut Array2D<T>,
+    from: ArrayPosition,
+    to: ArrayPosition,
+    value: T,
+) {
+    let (mut row, mut col) = (from.0 as i64, from.1 as i64);
+    let (end_row, end_col) = (to.0 as i64, to.1 as i64);
+    let d_row = -(end_row - row).abs();
+    let d_col = (end_col - col).abs();
+    let step_row = if row < end_row { 1 } else { -1 };
+    let step_col = if col < end_col { 1 } else { -1 };
+    let mut error = d_col + d_row;
+    loop {
+        if let Some(cell) = grid.get_mut(row as usize, col as usize) {
+            *cell = value.clone();
+        }
+        if (row, col) == (end_row, end_col) {
+            break;
+        }
+        let doubled = 2 * error;
+        if doubled >= d_row {
+            error += d_row;
+            col += step_col;
+        }
+        if doubled <= d_col {
+            error += d_col;
+            row += step_row;
+        }
+    }
+}
+
+/// Draw the closed outline through the vertices, joining the last vertex back to the first
+/// Every edge must be horizontal or vertical
+pub fn draw_rectilinear_edges<T: Clone>(
+    grid: &mut Array2D<T>,
+    vertices: &[ArrayPosition],
+    value: T,
+) -> Result<(), String> {
+    for (from, to) in polygon_edges(vertices) {
+        if from.0 != to.0 && from.1 != to.1 {
+            return Err(format!(
+                "Edge {from:?} -> {to:?} is not horizontal or vertical"
+            ));
+        }
+    }
+    for (from, to) in polygon_edges(vertices) {
+        draw_line(grid, from, to, value.clone());
+    }
+    Ok(())
+}
+
+/// Fill the polygon with the given vertices (in order, either winding) including its outline
+/// Cells are filled if their centre is inside the polygon using the even-odd rule, so holes made by
+/// self intersecting outlines are left empty
+pub fn fill_polygon<T: Clone>(grid: &mut Array2D<T>, vertices: &[ArrayPosition], value: T) {
+    for (from, to) in polygon_edges(vertices) {
+        draw_line(grid, from, to, value.clone());
+    }
+
+    let mut crossings = Vec::new();
+    for row in 0..grid.num_rows() as i64 {
+        // Scan just below the cell centres, so each edge is counted on rows top <= row < bottom
+        // and a vertex shared by two edges is never counted twice
+        crossings.clear();
+        for (from, to) in polygon_edges(vertices) {
+            let (top, bottom) = if from.0 < to.0 {
+                (from, to)
+            } else {
+                (to, from)
+            };
+            let (top_row, bottom_row) = (top.0 as i64, bottom.0 as i64);
+            if top_row <= row && row < bottom_row {
+                let fraction = (row - top_row) as f64 / (bottom_row - top_row) as f64;
+                crossings.push(top.1 as f64 + fraction * (bottom.1 as f64 - top.1 as f64));
+            }
+        }
+        crossings.sort_by(f64::total_cmp);
+        for pair in crossings.chunks_exact(2) {
+            let first = (pair[0].ceil().max(0.0)) as usize;
+            let last = pair[1].floor().min(grid.num_columns() as f64 - 1.0);
+            if last < 0.0 {
+                continue;
+            }
+            for col in first..=last as usize {
+                grid[(row as usize, col)] = value.clone();
+            }
+        }
+    }
+}
+
+// Each edge of the closed polygon as (from, to)
+pub(crate) fn polygon_edges<P: Copy>(vertices: &[P]) -> impl Iterator<Item = (P, P)> + '_ {
+    vertices
+        .iter()
+        .copied()
+        .zip(vertices.iter().copied().cycle().skip(1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::array2d::grid_from_lines;
+
+    #[test]
+    fn test_draw_line() {
+        let mut grid = Array2D::filled_with(b'.', 3, 5);
+        draw_line(&mut grid, (0, 0), (2, 4), b'#');
+        assert_eq!(grid, grid_from_lines(&["#....", ".##..", "...##"]));
+
+        // Direction doesn't matter for straight lines
+        let mut grid = Array2D::filled_with(b'.', 3, 5);
+        draw_line(&mut grid, (2, 3), (0, 3), b'#');
+        draw_line(&mut grid, (1, 4), (1, 0), b'#');
+        assert_eq!(grid, grid_from_lines(&["...#.", "#####", "...#."]));
+    }
+
+    #[test]
+    fn test_draw_rectilinear_edges() {
+        let mut grid = Array2D::filled_with(b'.', 4, 5);
+        let vertices = [(0, 0), (0, 4), (3, 4), (3, 0)];
+        draw_rectilinear_edges(&mut grid, &vertices, b'#').unwrap();
+        assert_eq!(grid, grid_from_lines(&["#####", "#...#", "#...#", "#####"]));
+
+        assert!(draw_rectilinear_edges(&mut grid, &[(0, 0), (1, 1), (1, 0)], b'#').is_err());
+    }
+
+    #[test]
+    fn test_fill_polygon_rectilinear() {
+        // Day 9 sample, in (row, col)
+        let vertices = [
+            (1, 7),
+            (1, 11),
+            (7, 11),
+            (7, 9),
+            (5, 9),
+            (5, 2),
+            (3, 2),
+            (3, 7),
+        ];
+        let mut grid = Array2D::filled_with(b'.', 9, 14);
+        fill_polygon(&mut grid, &vertices, b'#');
+        assert_eq!(
+            grid,
+            grid_from_lines(&[
+                "..............",
+                ".......#####..",
+                ".......#####..",
+                "..##########..",
+                "..##########..",
+                "..##########..",
+                ".........###..",
+                ".........###..",
+                "..............",
+            ])
+        );
+    }
+
+    #[test]
+    fn test_fill_polygon_concave() {
+        // U shape, the notch must stay empty
+        let vertices = [
+            (0, 0),
+            (0, 6),
+            (4, 6),
+            (4, 4),
+            (1, 4),
+            (1, 2),
+            (4, 2),
+            (4, 0),
+        ];
+        let mut grid = Array2D::filled_with(b'.', 5, 7);
+        fill_polygon(&mut grid, &vertices, b'#');
+        assert_eq!(
+            grid,
+            grid_from_lines(&["#######", "#######", "###.###", "###.###", "###.###"])
+        );
+    }
+
+    #[test]
+    fn test_fill_polygon_triangle() {
+        let vertices = [(0, 0), (4, 0), (4, 4)];
+        let mut grid = Array2D::filled_with(b'.', 5, 5);
+        fill_polygon(&mut grid, &vertices, b'#');
+        assert_eq!(
+            grid,
+            grid_from_lines(&["#....", "##...", "###..", "####.", "#####"])
+        );
+    }
+}
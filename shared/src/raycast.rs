@@ -0,0 +1,124 @@
+use array2d::Array2D;
+
+use crate::{ArrayPosition, Directions};
+
+/// Walks from a start position by a fixed (row, col) step until it leaves the grid
+/// The start position itself is not yielded
+#[derive(Debug, Clone)]
+pub struct Ray {
+    pos: ArrayPosition,
+    step: (i64, i64),
+    num_rows: usize,
+    num_columns: usize,
+    finished: bool,
+}
+
+impl Ray {
+    pub fn new<T>(grid: &Array2D<T>, start: ArrayPosition, step: (i64, i64)) -> Self {
+        Ray {
+            pos: start,
+            step,
+            num_rows: grid.num_rows(),
+            num_columns: grid.num_columns(),
+            // A zero step would never leave the grid
+            finished: step == (0, 0),
+        }
+    }
+    pub fn in_direction<T>(grid: &Array2D<T>, start: ArrayPosition, direction: Directions) -> Self {
+        Ray::new(grid, start, direction.get_direction())
+    }
+}
+
+impl Iterator for Ray {
+    type Item = ArrayPosition;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+        let row = self.pos.0 as i64 + self.step.0;
+        let col = self.pos.1 as i64 + self.step.1;
+        if row < 0 || col < 0 || row >= self.num_rows as i64 || col >= self.num_columns as i64 {
+            self.finished = true;
+            return None;
+        }
+        self.pos = (row as usize, col as usize);
+        Some(self.pos)
+    }
+}
+
+/// Walk from start by step, returning the first cell that the stop predicate is true for
+/// None if the ray left the grid without stopping
+pub fn cast_ray<T, F>(
+    grid: &Array2D<T>,
+    start: ArrayPosition,
+    step: (i64, i64),
+    stop: F,
+) -> Option<ArrayPosition>
+where
+    F: Fn(&T) -> bool,
+{
+    Ray::new(grid, start, step).find(|pos| stop(&grid[*pos]))
+}
+
+/// Can a be seen from b without any blocking cell exactly on the straight line between them
+/// Only the lattice points on the line are checked, so (0,0) -> (2,4) looks at (1,2)
+/// The end cells themselves are never considered blocking
+pub fn line_of_sight<T, F>(grid: &Array2D<T>, a: ArrayPosition, b: ArrayPosition, blocks: F) -> bool
+where
+    F: Fn(&T) -> bool,
+{
+    let delta = (b.0 as i64 - a.0 as i64, b.1 as i64 - a.1 as i64);
+    let steps = gcd(delta.0.unsigned_abs(), delta.1.unsigned_abs()) as usize;
+    if steps <= 1 {
+        return true;
+    }
+    let step = (delta.0 / steps as i64, delta.1 / steps as i64);
+    Ray::new(grid, a, step)
+        .take(steps - 1)
+        .all(|pos| !blocks(&grid[pos]))
+}
+
+pub(crate) fn gcd(a: u64, b: u64) -> u64 {
+    if b == 0 { a } else { gcd(b, a % b) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::array2d::grid_from_lines;
+
+    #[test]
+    fn test_ray_walks_to_edge() {
+        let grid = Array2D::filled_with(b'.', 3, 4);
+        let cells: Vec<_> = Ray::in_direction(&grid, (1, 1), Directions::RIGHT).collect();
+        assert_eq!(cells, vec![(1, 2), (1, 3)]);
+        let cells: Vec<_> = Ray::in_direction(&grid, (1, 1), Directions::UP).collect();
+        assert_eq!(cells, vec![(0, 1)]);
+        let cells: Vec<_> = Ray::new(&grid, (0, 0), (1, 2)).collect();
+        assert_eq!(cells, vec![(1, 2)]);
+        assert_eq!(Ray::new(&grid, (0, 0), (0, 0)).count(), 0);
+    }
+
+    #[test]
+    fn test_cast_ray() {
+        let grid = grid_from_lines(&["..S..", ".....", "..^..", "....."]);
+        let down = Directions::DOWN.get_direction();
+        assert_eq!(cast_ray(&grid, (0, 2), down, |c| *c == b'^'), Some((2, 2)));
+        assert_eq!(cast_ray(&grid, (0, 1), down, |c| *c == b'^'), None);
+    }
+
+    #[test]
+    fn test_line_of_sight() {
+        let grid = grid_from_lines(&["#....", "..#..", "....#"]);
+        let blocks = |c: &u8| *c == b'#';
+        // Straight through the # at (1,2)
+        assert!(!line_of_sight(&grid, (0, 0), (2, 4), blocks));
+        assert!(!line_of_sight(&grid, (2, 4), (0, 0), blocks));
+        // The line from (0,1) to (2,4) has no lattice points between them
+        assert!(line_of_sight(&grid, (0, 1), (2, 4), blocks));
+        assert!(line_of_sight(&grid, (0, 1), (0, 4), blocks));
+        assert!(!line_of_sight(&grid, (1, 0), (1, 4), blocks));
+        assert!(line_of_sight(&grid, (1, 1), (1, 1), blocks));
+    }
+}
@@ -1,6 +1,8 @@
+use std::collections::HashMap;
+
 use array2d::Array2D;
 
-use crate::{ChallengeDay, input_files::Question};
+use crate::{ArrayPosition, ChallengeDay, input_files::Question};
 
 pub fn get_question_data_lines(day: ChallengeDay, question: Question) -> Vec<String> {
     let file_path = day.get_question_file_path(question);
@@ -70,18 +72,117 @@ pub fn get_question_data_to_grid(day: ChallengeDay, question: Question) -> Array
     .unwrap()
 }
 
-pub fn get_question_data_to_num_grid(day: ChallengeDay, question: Question) -> Array2D<i64> {
+/// Load the grid, along with the positions (in row major order) of each of the marker bytes
+/// Every marker gets an entry, even if it doesn't appear in the grid
+/// If `replace_with` is set the markers are overwritten with it, i.e. to turn `S` and `E` back into open floor
+pub fn get_question_data_to_grid_with_markers(
+    day: ChallengeDay,
+    question: Question,
+    markers: &[u8],
+    replace_with: Option<u8>,
+) -> (Array2D<u8>, HashMap<u8, Vec<ArrayPosition>>) {
+    let mut grid = get_question_data_to_grid(day, question);
+    let positions = find_grid_markers(&mut grid, markers, replace_with);
+    (grid, positions)
+}
+
+pub fn find_grid_markers(
+    grid: &mut Array2D<u8>,
+    markers: &[u8],
+    replace_with: Option<u8>,
+) -> HashMap<u8, Vec<ArrayPosition>> {
+    let mut positions: HashMap<u8, Vec<ArrayPosition>> =
+        markers.iter().map(|marker| (*marker, Vec::new())).collect();
+    for (pos, value) in grid.enumerate_row_major() {
+        if let Some(found) = positions.get_mut(value) {
+            found.push(pos);
+        }
+    }
+    if let Some(replace_with) = replace_with {
+        for pos in positions.values().flatten() {
+            grid[*pos] = replace_with;
+        }
+    }
+    positions
+}
+
+/// Load a grid of single digit numbers, i.e. `0123` -> [0, 1, 2, 3]
+pub fn get_question_data_to_num_grid(
+    day: ChallengeDay,
+    question: Question,
+) -> Result<Array2D<i64>, String> {
     let file_path = day.get_question_file_path(question);
-    let file_contents = std::fs::read_to_string(&file_path).unwrap();
-    let lines: Vec<&str> = file_contents.lines().collect();
-    let filtered_file = file_contents.replace("\n", "").replace("\r", "");
-    let values: Vec<i64> = filtered_file
-        .as_bytes()
-        .iter()
-        .map(|&a| (a - b'0') as i64)
-        .collect();
+    let file_contents = std::fs::read_to_string(&file_path)
+        .map_err(|e| format!("Failed to read file: {file_path} ({e})"))?;
+    parse_digit_grid(&file_contents)
+}
+
+/// Load a grid of numbers separated by whitespace and/or commas, i.e. `10, -2, 300`
+pub fn get_question_data_to_number_grid(
+    day: ChallengeDay,
+    question: Question,
+) -> Result<Array2D<i64>, String> {
+    let file_path = day.get_question_file_path(question);
+    let file_contents = std::fs::read_to_string(&file_path)
+        .map_err(|e| format!("Failed to read file: {file_path} ({e})"))?;
+    parse_number_grid(&file_contents)
+}
 
-    Array2D::from_row_major(&values, lines[0].len(), lines.len()).unwrap()
+pub fn parse_digit_grid(contents: &str) -> Result<Array2D<i64>, String> {
+    let rows = contents
+        .lines()
+        .enumerate()
+        .map(|(row, line)| {
+            line.bytes()
+                .enumerate()
+                .map(|(col, digit)| match digit {
+                    b'0'..=b'9' => Ok((digit - b'0') as i64),
+                    _ => Err(format!(
+                        "Invalid digit {:?} at row {row}, column {col}",
+                        digit as char
+                    )),
+                })
+                .collect::<Result<Vec<i64>, String>>()
+        })
+        .collect::<Result<Vec<Vec<i64>>, String>>()?;
+    rows_to_grid(rows)
+}
+
+pub fn parse_number_grid(contents: &str) -> Result<Array2D<i64>, String> {
+    let rows = contents
+        .lines()
+        .enumerate()
+        .map(|(row, line)| {
+            line.split(|c: char| c == ',' || c.is_whitespace())
+                .filter(|value| !value.is_empty())
+                .map(|value| {
+                    value
+                        .parse::<i64>()
+                        .map_err(|_| format!("Invalid number {value:?} on row {row}"))
+                })
+                .collect::<Result<Vec<i64>, String>>()
+        })
+        .collect::<Result<Vec<Vec<i64>>, String>>()?;
+    rows_to_grid(rows)
+}
+
+// Ragged rows are an error rather than being padded
+fn rows_to_grid(rows: Vec<Vec<i64>>) -> Result<Array2D<i64>, String> {
+    let Some(first_row) = rows.first() else {
+        return Err("No rows in grid".to_string());
+    };
+    if let Some((row, values)) = rows
+        .iter()
+        .enumerate()
+        .find(|(_, values)| values.len() != first_row.len())
+    {
+        return Err(format!(
+            "Row {row} has {} values, expected {}",
+            values.len(),
+            first_row.len()
+        ));
+    }
+    Array2D::from_rows(&rows).map_err(|e| format!("{e:?}"))
 }
 
 #[cfg(test)]
@@ -144,4 +245,60 @@ mod tests {
         assert_eq!(line_groups[1][3], "...2.");
         assert_eq!(line_groups[1][4], "...2.");
     }
+
+    #[test]
+    fn test_get_question_data_to_num_grid() {
+        // Non square, 2 rows of 10
+        let grid = get_question_data_to_num_grid(ChallengeDay::Test, Question::Question).unwrap();
+        assert_eq!(grid.num_rows(), 2);
+        assert_eq!(grid.num_columns(), 10);
+        assert_eq!(grid[(0, 3)], 3);
+        assert_eq!(grid[(1, 0)], 9);
+
+        let error = get_question_data_to_num_grid(ChallengeDay::Test, Question::Sample);
+        assert!(error.is_err());
+    }
+
+    #[test]
+    fn test_parse_digit_grid() {
+        let grid = parse_digit_grid("123\r\n456\r\n").unwrap();
+        assert_eq!(grid.as_rows(), vec![vec![1, 2, 3], vec![4, 5, 6]]);
+
+        assert!(parse_digit_grid("123\n45\n").is_err());
+        assert!(parse_digit_grid("12a\n456\n").is_err());
+        assert!(parse_digit_grid("").is_err());
+    }
+
+    #[test]
+    fn test_parse_number_grid() {
+        let grid = parse_number_grid("10 200  -3\n4,5, 60\n").unwrap();
+        assert_eq!(grid.as_rows(), vec![vec![10, 200, -3], vec![4, 5, 60]]);
+
+        assert!(parse_number_grid("1 2 3\n4 5\n").is_err());
+        assert!(parse_number_grid("1 2 x\n").is_err());
+    }
+
+    #[test]
+    fn test_get_question_data_to_grid_with_markers() {
+        let (grid, markers) = get_question_data_to_grid_with_markers(
+            ChallengeDay::Test,
+            Question::Sample,
+            b"AEZ",
+            Some(b'.'),
+        );
+        assert_eq!(markers[&b'A'], vec![(0, 0)]);
+        assert_eq!(markers[&b'E'], vec![(1, 1)]);
+        assert!(markers[&b'Z'].is_empty());
+        assert_eq!(grid.as_rows(), vec![b".BC".to_vec(), b"D.F".to_vec()]);
+    }
+
+    #[test]
+    fn test_find_grid_markers_keeps_markers() {
+        let mut grid = Array2D::from_rows(&[b"S.S".to_vec(), b"..E".to_vec()]).unwrap();
+        let original = grid.clone();
+        let markers = find_grid_markers(&mut grid, b"SE", None);
+        assert_eq!(markers[&b'S'], vec![(0, 0), (0, 2)]);
+        assert_eq!(markers[&b'E'], vec![(1, 2)]);
+        assert_eq!(grid, original);
+    }
 }
@@ -0,0 +1,153 @@
+use array2d::Array2D;
+
+use crate::{Adjacents, GridBounds, flood_region};
+
+/// A connected area of equal valued cells, as found by `label_regions`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Region<T> {
+    pub label: usize,
+    pub value: T,
+    pub area: usize,
+    /// Number of cell edges that border another region or the edge of the grid
+    pub perimeter: usize,
+    /// Number of straight sides of the outline (including the outlines of any holes)
+    pub sides: usize,
+    pub bounds: GridBounds,
+    pub touches_border: bool,
+}
+
+/// Split the grid into connected regions of equal values
+/// Returns a grid of labels, where each label is the index of that cell's region in the returned list
+pub fn label_regions<T>(
+    array: &Array2D<T>,
+    connectivity: Adjacents,
+) -> (Array2D<usize>, Vec<Region<T>>)
+where
+    T: Clone + PartialEq,
+{
+    let (num_rows, num_columns) = (array.num_rows(), array.num_columns());
+    let mut labels = Array2D::filled_with(usize::MAX, num_rows, num_columns);
+    let mut regions = Vec::new();
+
+    for (pos, value) in array.enumerate_row_major() {
+        if labels[pos] != usize::MAX {
+            continue;
+        }
+        let label = regions.len();
+        let found = flood_region(array, pos, connectivity, |other| other == value);
+        for cell in &found.cells {
+            labels[*cell] = label;
+        }
+        regions.push(Region {
+            label,
+            value: value.clone(),
+            area: found.count(),
+            perimeter: 0,
+            sides: 0,
+            bounds: found.bounds.unwrap(),
+            touches_border: false,
+        });
+    }
+
+    // With every cell labelled, walk the grid once more to measure the outlines
+    let same_label = |label: usize, row: i64, col: i64| -> bool {
+        row >= 0
+            && col >= 0
+            && (row as usize) < num_rows
+            && (col as usize) < num_columns
+            && labels[(row as usize, col as usize)] == label
+    };
+    for (pos, label) in labels.enumerate_row_major() {
+        let region = &mut regions[*label];
+        let (row, col) = (pos.0 as i64, pos.1 as i64);
+
+        region.perimeter += Adjacents::CROSS
+            .get_pattern()
+            .iter()
+            .filter(|(dr, dc)| !same_label(*label, row + dr, col + dc))
+            .count();
+
+        // Every corner of the outline starts a new side, so count corners
+        for (dr, dc) in Adjacents::DIAGONAL.get_pattern() {
+            let vertical = same_label(*label, row + dr, col);
+            let horizontal = same_label(*label, row, col + dc);
+            let diagonal = same_label(*label, row + dr, col + dc);
+            let outside_corner = !vertical && !horizontal;
+            let inside_corner = vertical && horizontal && !diagonal;
+            if outside_corner || inside_corner {
+                region.sides += 1;
+            }
+        }
+
+        if pos.0 == 0 || pos.1 == 0 || pos.0 == num_rows - 1 || pos.1 == num_columns - 1 {
+            region.touches_border = true;
+        }
+    }
+
+    (labels, regions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::array2d::grid_from_lines;
+
+    #[test]
+    fn test_label_regions_simple() {
+        let grid = grid_from_lines(&["AAAA", "BBCD", "BBCC", "EEEC"]);
+        let (labels, regions) = label_regions(&grid, Adjacents::CROSS);
+        assert_eq!(regions.len(), 5);
+        for (pos, label) in labels.enumerate_row_major() {
+            assert_eq!(regions[*label].value, grid[pos]);
+        }
+
+        let summary: Vec<(u8, usize, usize, usize)> = regions
+            .iter()
+            .map(|r| (r.value, r.area, r.perimeter, r.sides))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (b'A', 4, 10, 4),
+                (b'B', 4, 8, 4),
+                (b'C', 4, 10, 8),
+                (b'D', 1, 4, 4),
+                (b'E', 3, 8, 4),
+            ]
+        );
+
+        let c = &regions[2];
+        assert_eq!((c.bounds.min, c.bounds.max), ((1, 2), (3, 3)));
+        assert!(c.touches_border);
+    }
+
+    #[test]
+    fn test_label_regions_holes() {
+        let grid = grid_from_lines(&["OOOOO", "OXOXO", "OOOOO", "OXOXO", "OOOOO"]);
+        let (_, regions) = label_regions(&grid, Adjacents::CROSS);
+        assert_eq!(regions.len(), 5);
+        let outer = &regions[0];
+        assert_eq!(outer.area, 21);
+        assert_eq!(outer.perimeter, 36);
+        // 4 outer sides plus 4 for each hole
+        assert_eq!(outer.sides, 20);
+        assert!(outer.touches_border);
+
+        let hole = &regions[1];
+        assert_eq!((hole.area, hole.perimeter, hole.sides), (1, 4, 4));
+        assert!(!hole.touches_border);
+    }
+
+    #[test]
+    fn test_label_regions_connectivity() {
+        let grid = grid_from_lines(&["#.", ".#"]);
+        let (_, cross) = label_regions(&grid, Adjacents::CROSS);
+        assert_eq!(cross.len(), 4);
+
+        let (labels, all) = label_regions(&grid, Adjacents::ALL);
+        assert_eq!(all.len(), 2);
+        assert_eq!(labels[(0, 0)], labels[(1, 1)]);
+        assert_eq!(all[labels[(0, 0)]].area, 2);
+        assert_eq!(all[labels[(0, 0)]].sides, 8);
+    }
+}
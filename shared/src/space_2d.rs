@@ -1,18 +1,151 @@
-#[derive(Debug, Clone, Copy, PartialEq)]
-pub struct XYPos {
-    pub x: f64,
-    pub y: f64,
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+use std::str::FromStr;
+
+use num_traits::{Float, Num};
+
+/// Point in 2D space, f64 by default but any number type works
+/// Integer positions also get Eq, Hash and Ord so they can be used as keys
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct XYPos<T = f64> {
+    pub x: T,
+    pub y: T,
 }
 
-impl XYPos {
-    pub fn new(x: f64, y: f64) -> Self {
+impl<T> XYPos<T> {
+    pub fn new(x: T, y: T) -> Self {
         XYPos { x, y }
     }
-    pub fn distance_to(&self, other: &XYPos) -> f64 {
-        ((self.x - other.x).powi(2) + (self.y - other.y).powi(2)).sqrt()
+}
+
+impl<T: Num + Copy + PartialOrd> XYPos<T> {
+    pub fn move_by(&mut self, dx: T, dy: T) {
+        self.x = self.x + dx;
+        self.y = self.y + dy;
+    }
+    /// Straight line distance squared, cheaper than distance_to when only comparing distances
+    pub fn squared_distance_to(&self, other: &XYPos<T>) -> T {
+        let (dx, dy) = (abs_diff(self.x, other.x), abs_diff(self.y, other.y));
+        dx * dx + dy * dy
+    }
+    pub fn manhattan_distance_to(&self, other: &XYPos<T>) -> T {
+        abs_diff(self.x, other.x) + abs_diff(self.y, other.y)
+    }
+}
+
+impl<T: Float> XYPos<T> {
+    pub fn distance_to(&self, other: &XYPos<T>) -> T {
+        self.squared_distance_to(other).sqrt()
+    }
+}
+
+// Works for unsigned types too
+pub(crate) fn abs_diff<T: Num + PartialOrd>(a: T, b: T) -> T {
+    if a > b { a - b } else { b - a }
+}
+
+impl<T: Add<Output = T>> Add for XYPos<T> {
+    type Output = XYPos<T>;
+    fn add(self, other: XYPos<T>) -> XYPos<T> {
+        XYPos::new(self.x + other.x, self.y + other.y)
+    }
+}
+impl<T: Sub<Output = T>> Sub for XYPos<T> {
+    type Output = XYPos<T>;
+    fn sub(self, other: XYPos<T>) -> XYPos<T> {
+        XYPos::new(self.x - other.x, self.y - other.y)
+    }
+}
+impl<T: Mul<Output = T> + Copy> Mul<T> for XYPos<T> {
+    type Output = XYPos<T>;
+    fn mul(self, scale: T) -> XYPos<T> {
+        XYPos::new(self.x * scale, self.y * scale)
+    }
+}
+impl<T: Neg<Output = T>> Neg for XYPos<T> {
+    type Output = XYPos<T>;
+    fn neg(self) -> XYPos<T> {
+        XYPos::new(-self.x, -self.y)
+    }
+}
+impl<T: AddAssign> AddAssign for XYPos<T> {
+    fn add_assign(&mut self, other: XYPos<T>) {
+        self.x += other.x;
+        self.y += other.y;
+    }
+}
+impl<T: SubAssign> SubAssign for XYPos<T> {
+    fn sub_assign(&mut self, other: XYPos<T>) {
+        self.x -= other.x;
+        self.y -= other.y;
+    }
+}
+
+/// Parse from "x,y"
+impl<T: FromStr> FromStr for XYPos<T> {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let [x, y]: [T; 2] = parse_coordinates(s)?
+            .try_into()
+            .map_err(|_| format!("Expected x,y but got '{s}'"))?;
+        Ok(XYPos::new(x, y))
+    }
+}
+
+// Split on commas and parse each part, only so XYPos and XYZPos share the error handling
+pub(crate) fn parse_coordinates<T: FromStr>(s: &str) -> Result<Vec<T>, String> {
+    s.split(',')
+        .map(|part| {
+            part.trim()
+                .parse()
+                .map_err(|_| format!("Can't parse '{part}' in '{s}'"))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_xy_pos_distances() {
+        let a = XYPos::new(1, 2);
+        let b = XYPos::new(4, -2);
+        assert_eq!(a.squared_distance_to(&b), 25);
+        assert_eq!(a.manhattan_distance_to(&b), 7);
+        assert_eq!(
+            XYPos::new(1.0, 2.0).distance_to(&XYPos::new(4.0, -2.0)),
+            5.0
+        );
+        assert_eq!(
+            XYPos::<u32>::new(5, 1).manhattan_distance_to(&XYPos::new(2, 3)),
+            5
+        );
+        assert_eq!(
+            XYPos::<u32>::new(1, 1).squared_distance_to(&XYPos::new(3, 3)),
+            8
+        );
+    }
+
+    #[test]
+    fn test_xy_pos_operators() {
+        let mut a = XYPos::new(1, 2);
+        assert_eq!(a + XYPos::new(3, 4), XYPos::new(4, 6));
+        assert_eq!(a - XYPos::new(3, 4), XYPos::new(-2, -2));
+        assert_eq!(a * 3, XYPos::new(3, 6));
+        assert_eq!(-a, XYPos::new(-1, -2));
+        a += XYPos::new(1, 1);
+        a.move_by(1, 0);
+        assert_eq!(a, XYPos::new(3, 3));
+        a -= XYPos::new(3, 3);
+        assert_eq!(a, XYPos::default());
+        assert!(XYPos::new(1, 5) < XYPos::new(2, 0));
     }
-    pub fn move_by(&mut self, dx: f64, dy: f64) {
-        self.x += dx;
-        self.y += dy;
+
+    #[test]
+    fn test_xy_pos_parse() {
+        assert_eq!("3, -4".parse(), Ok(XYPos::new(3, -4)));
+        assert_eq!("1.5,2".parse(), Ok(XYPos::new(1.5, 2.0)));
+        assert!("1,2,3".parse::<XYPos<i64>>().is_err());
+        assert!("1,a".parse::<XYPos<i64>>().is_err());
     }
 }
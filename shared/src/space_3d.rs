@@ -1,21 +1,142 @@
-#[derive(Debug, Clone, Copy, PartialEq)]
-pub struct XYZPos {
-    pub x: f64,
-    pub y: f64,
-    pub z: f64,
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+use std::str::FromStr;
+
+use num_traits::{Float, Num};
+
+use crate::space_2d::{abs_diff, parse_coordinates};
+
+/// Point in 3D space, f64 by default but any number type works
+/// Integer positions also get Eq, Hash and Ord so they can be used as keys
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct XYZPos<T = f64> {
+    pub x: T,
+    pub y: T,
+    pub z: T,
 }
 
-impl XYZPos {
-    pub fn new(x: f64, y: f64, z: f64) -> Self {
+impl<T> XYZPos<T> {
+    pub fn new(x: T, y: T, z: T) -> Self {
         XYZPos { x, y, z }
     }
-    pub fn distance_to(&self, other: &XYZPos) -> f64 {
-        ((self.x - other.x).powi(2) + (self.y - other.y).powi(2) + (self.z - other.z).powi(2))
-            .sqrt()
+}
+
+impl<T: Num + Copy + PartialOrd> XYZPos<T> {
+    pub fn move_by(&mut self, dx: T, dy: T, dz: T) {
+        self.x = self.x + dx;
+        self.y = self.y + dy;
+        self.z = self.z + dz;
+    }
+    /// Straight line distance squared, cheaper than distance_to when only comparing distances
+    pub fn squared_distance_to(&self, other: &XYZPos<T>) -> T {
+        let (dx, dy, dz) = (
+            abs_diff(self.x, other.x),
+            abs_diff(self.y, other.y),
+            abs_diff(self.z, other.z),
+        );
+        dx * dx + dy * dy + dz * dz
+    }
+    pub fn manhattan_distance_to(&self, other: &XYZPos<T>) -> T {
+        abs_diff(self.x, other.x) + abs_diff(self.y, other.y) + abs_diff(self.z, other.z)
+    }
+}
+
+impl<T: Float> XYZPos<T> {
+    pub fn distance_to(&self, other: &XYZPos<T>) -> T {
+        self.squared_distance_to(other).sqrt()
+    }
+}
+
+impl<T: Add<Output = T>> Add for XYZPos<T> {
+    type Output = XYZPos<T>;
+    fn add(self, other: XYZPos<T>) -> XYZPos<T> {
+        XYZPos::new(self.x + other.x, self.y + other.y, self.z + other.z)
+    }
+}
+impl<T: Sub<Output = T>> Sub for XYZPos<T> {
+    type Output = XYZPos<T>;
+    fn sub(self, other: XYZPos<T>) -> XYZPos<T> {
+        XYZPos::new(self.x - other.x, self.y - other.y, self.z - other.z)
+    }
+}
+impl<T: Mul<Output = T> + Copy> Mul<T> for XYZPos<T> {
+    type Output = XYZPos<T>;
+    fn mul(self, scale: T) -> XYZPos<T> {
+        XYZPos::new(self.x * scale, self.y * scale, self.z * scale)
+    }
+}
+impl<T: Neg<Output = T>> Neg for XYZPos<T> {
+    type Output = XYZPos<T>;
+    fn neg(self) -> XYZPos<T> {
+        XYZPos::new(-self.x, -self.y, -self.z)
+    }
+}
+impl<T: AddAssign> AddAssign for XYZPos<T> {
+    fn add_assign(&mut self, other: XYZPos<T>) {
+        self.x += other.x;
+        self.y += other.y;
+        self.z += other.z;
+    }
+}
+impl<T: SubAssign> SubAssign for XYZPos<T> {
+    fn sub_assign(&mut self, other: XYZPos<T>) {
+        self.x -= other.x;
+        self.y -= other.y;
+        self.z -= other.z;
+    }
+}
+
+/// Parse from "x,y,z"
+impl<T: FromStr> FromStr for XYZPos<T> {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let [x, y, z]: [T; 3] = parse_coordinates(s)?
+            .try_into()
+            .map_err(|_| format!("Expected x,y,z but got '{s}'"))?;
+        Ok(XYZPos::new(x, y, z))
     }
-    pub fn move_by(&mut self, dx: f64, dy: f64, dz: f64) {
-        self.x += dx;
-        self.y += dy;
-        self.z += dz;
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    #[test]
+    fn test_xyz_pos_distances() {
+        let a = XYZPos::new(162, 817, 812);
+        let b = XYZPos::new(425, 690, 689);
+        assert_eq!(a.squared_distance_to(&b), 263 * 263 + 127 * 127 + 123 * 123);
+        assert_eq!(a.manhattan_distance_to(&b), 263 + 127 + 123);
+        let c = XYZPos::new(1.0, 2.0, 2.0);
+        assert_eq!(c.distance_to(&XYZPos::default()), 3.0);
+        let d = XYZPos::<u32>::new(1, 5, 1);
+        assert_eq!(d.squared_distance_to(&XYZPos::new(3, 3, 2)), 9);
+        assert_eq!(d.manhattan_distance_to(&XYZPos::new(3, 3, 2)), 5);
+    }
+
+    #[test]
+    fn test_xyz_pos_operators() {
+        let mut a = XYZPos::new(1, 2, 3);
+        assert_eq!(a + XYZPos::new(1, 1, 1), XYZPos::new(2, 3, 4));
+        assert_eq!(a - XYZPos::new(1, 1, 1), XYZPos::new(0, 1, 2));
+        assert_eq!(a * 2, XYZPos::new(2, 4, 6));
+        assert_eq!(-a, XYZPos::new(-1, -2, -3));
+        a += XYZPos::new(1, 0, 0);
+        a -= XYZPos::new(0, 2, 0);
+        a.move_by(0, 0, -3);
+        assert_eq!(a, XYZPos::new(2, 0, 0));
+    }
+
+    #[test]
+    fn test_xyz_pos_parse_and_hash() {
+        let points: Vec<XYZPos<i64>> = ["162,817,812", "57,618,57", "162,817,812"]
+            .iter()
+            .map(|line| line.parse().unwrap())
+            .collect();
+        assert_eq!(points[1], XYZPos::new(57, 618, 57));
+        let unique: HashSet<XYZPos<i64>> = points.into_iter().collect();
+        assert_eq!(unique.len(), 2);
+        assert!("1,2".parse::<XYZPos<i64>>().is_err());
     }
 }